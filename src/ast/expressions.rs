@@ -1,13 +1,26 @@
-// An expression.
+use super::*;
+
+// An expression, with its location in the source code.
+#[derive(Debug)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+// The different kinds of expressions.
 #[derive(Debug)]
-pub enum Expr {
+pub enum ExprKind {
     Assign(Box<Assign>),
+    Incr(Incr),
     Ternary(Box<Ternary>),
     Atom(Atom),
     LValue(LValue),
     ListInit(ListInit),
     StructInit(StructInit),
+    EnumInit(EnumInit),
     Call(Call),
+    // An anonymous function, capturing the variables in scope where it is evaluated.
+    Function(Box<Function>),
     BinExpr(Box<BinExpr>),
     UnExpr(Box<UnExpr>),
 }
@@ -24,6 +37,7 @@ pub struct LValue {
     pub name: &'static str,
     pub first_index: Index,
     pub path: Vec<(&'static str, Index)>,
+    pub span: Span,
 }
 
 // A ternary expression.
@@ -38,9 +52,26 @@ pub struct Ternary {
 #[derive(Debug)]
 pub struct Assign {
     pub lvalue: LValue,
+    // The operator of a compound assignment, like += or <<=.
+    pub op: Option<BinOp>,
     pub expr: Expr,
 }
 
+// An increment or a decrement, like i++ or --i.
+#[derive(Debug)]
+pub struct Incr {
+    pub lvalue: LValue,
+    pub op: IncrOp,
+    // A prefix increment evaluates to the new value, a postfix one to the old value.
+    pub prefix: bool,
+}
+
+// An increment or decrement operator.
+#[derive(Copy, Clone, Debug)]
+pub enum IncrOp {
+    Inc, Dec,
+}
+
 // A list initialization.
 #[derive(Debug)]
 pub struct ListInit {
@@ -55,6 +86,14 @@ pub struct StructInit {
     pub fields: Vec<(&'static str, Expr)>,
 }
 
+// An enum value, like Shape::Circle(1.0).
+#[derive(Debug)]
+pub struct EnumInit {
+    pub name: &'static str,
+    pub variant: &'static str,
+    pub args: Vec<Expr>,
+}
+
 // The atomic value of a primitive.
 #[derive(Debug)]
 pub enum Atom {
@@ -66,11 +105,12 @@ pub enum Atom {
     String(String),
 }
 
-// A call expression. Can only call builtins for now.
+// A call expression, to a builtin, a function, or a variable holding a function.
 #[derive(Debug)]
 pub struct Call {
     pub name: &'static str,
     pub args: Vec<Expr>,
+    pub span: Span,
 }
 
 // A binary expression.
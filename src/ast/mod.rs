@@ -1,5 +1,7 @@
 use crate::utils::*;
 
+pub use crate::sources::Span;
+
 mod expressions;
 pub use expressions::*;
 
@@ -16,8 +18,13 @@ pub use types::*;
 #[derive(Default, Debug)]
 pub struct AST {
     pub names: Map<Name>,
+    // The location of the definition of each name.
+    pub spans: Map<Span>,
+    // The doc comments of the names defined with one, without the comment markers.
+    pub docs: Map<String>,
     pub init: Vec<&'static str>,
     pub run: Vec<&'static str>,
+    pub shutdown: Vec<&'static str>,
 }
 
 // A name in the global namespace.
@@ -27,11 +34,17 @@ pub enum Name {
     System(System),
     Component(StructDef),
     Resource(StructDef),
+    Event(StructDef),
     Struct(StructDef),
+    Enum(EnumDef),
 }
 
 #[derive(Debug)]
 pub struct Function {
     pub args: Vec<&'static str>,
+    // The types of the arguments, for the ones declared with a type.
+    pub types: Vec<Option<Type>>,
+    // The type of the returned value, if the function declares one.
+    pub returns: Option<Type>,
     pub body: Block,
 }
\ No newline at end of file
@@ -1,8 +1,15 @@
 use super::*;
 
-// A statement.
+// A statement, with its location in the source code.
 #[derive(Debug)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+// The different kinds of statements.
+#[derive(Debug)]
+pub enum StatementKind {
     Decl(Decl),
     If(If),
     For(For),
@@ -59,14 +66,30 @@ pub struct Query {
 pub struct Switch {
     pub expr: Expr,
     pub cases: Vec<SwitchCase>,
-    pub default: Block,
+    pub default: Option<Block>,
 }
 
 // A switch case.
 #[derive(Debug)]
 pub struct SwitchCase {
-    pub val: Atom,
+    pub pattern: Pattern,
     pub block: Block,
+    pub span: Span,
+}
+
+// The value a switch case matches.
+#[derive(Debug)]
+pub enum Pattern {
+    Atom(Atom),
+    Variant(VariantPattern),
+}
+
+// A pattern matching a variant of an enum, binding the values it holds.
+#[derive(Debug)]
+pub struct VariantPattern {
+    pub name: &'static str,
+    pub variant: &'static str,
+    pub bindings: Vec<&'static str>,
 }
 
 // A code block.
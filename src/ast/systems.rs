@@ -5,6 +5,7 @@ use super::*;
 pub struct System {
     pub filter: Filter,
     pub code: Block,
+    pub span: Span,
 }
 
 // A single Filter.
@@ -12,13 +13,24 @@ pub struct System {
 pub struct Filter {
     pub entities: Option<EntityFilter>,
     pub resources: Vec<Argument>,
+    // Events sent since the system last ran, bound as lists.
+    pub events: Vec<Argument>,
 }
 
 // An entity filter.
 #[derive(Debug)]
 pub struct EntityFilter {
     pub name: &'static str,
+    // Components the entities must have.
     pub args: Vec<Argument>,
+    // Components the entities must not have.
+    pub without: Vec<&'static str>,
+    // Components bound if the entities have them, or void otherwise.
+    pub optional: Vec<Argument>,
+    // Components the entities must have, added since the system last ran.
+    pub added: Vec<&'static str>,
+    // Components the entities must have, added or changed since the system last ran.
+    pub changed: Vec<&'static str>,
 }
 
 // An argument to a System or function signature.
@@ -26,4 +38,5 @@ pub struct EntityFilter {
 pub struct Argument {
     pub ty: &'static str,
     pub name: &'static str,
+    pub span: Span,
 }
\ No newline at end of file
@@ -5,10 +5,33 @@ use super::*;
 #[derive(Default, Debug)]
 pub struct StructDef {
     pub fields: Map<Type>,
+    // The location of the type of each field.
+    pub spans: Map<Span>,
 }
 
-// A primitive type.
-#[derive(PartialEq, Eq, Debug)]
+// An enum definition.
+#[derive(Default, Debug)]
+pub struct EnumDef {
+    pub variants: Vec<Variant>,
+}
+
+impl EnumDef {
+    // Returns the variant with the given name, if there is one.
+    pub fn variant(&self, name: &str) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+}
+
+// A variant of an enum, with the types of the values it holds.
+#[derive(Debug)]
+pub struct Variant {
+    pub name: &'static str,
+    pub fields: Vec<Type>,
+    pub span: Span,
+}
+
+// A primitive type, or the name of a struct-like type or of an enum.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Type  {
     Void,
     Bool,
@@ -18,6 +41,7 @@ pub enum Type  {
     String,
     List,
     Entity,
+    Function,
     Struct(&'static str),
 }
 
@@ -32,6 +56,7 @@ impl fmt::Display for Type {
             Type::String => write!(f, "string"),
             Type::List => write!(f, "list"),
             Type::Entity => write!(f, "entity"),
+            Type::Function => write!(f, "function"),
             Type::Struct(name) => write!(f, "{}", name),
         }
     }
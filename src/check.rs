@@ -0,0 +1,524 @@
+use anyhow::{anyhow, Error, Result};
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::ast::{self, Span};
+use crate::eval::{Context, Def};
+use crate::sources::WithSpan;
+use crate::utils::*;
+
+// Checks the program before it runs, reporting all the errors found at once.
+// Problems that don't prevent the program from running are added to the warnings.
+pub fn check(ast: &'static ast::AST, ctx: &Context, warnings: &mut Vec<Error>) -> Result<()> {
+    let mut checker = Checker {ctx, locals: HashSet::new(), errors: Vec::new(), warnings: Vec::new()};
+    checker.check_ast(ast);
+    checker.report(warnings)
+}
+
+// Checks statements run outside of any function or system, like the ones typed in the REPL,
+// where the given variables already exist.
+pub fn check_statements(block: &'static ast::Block, ctx: &Context, vars: Vec<&'static str>, warnings: &mut Vec<Error>) -> Result<()> {
+    let mut checker = Checker {ctx, locals: vars.into_iter().collect(), errors: Vec::new(), warnings: Vec::new()};
+    checker.check_block(block);
+    checker.report(warnings)
+}
+
+// Sorts errors by location, for the report not to depend on the order of the names.
+fn sorted(mut errors: Vec<(Option<Span>, Error)>) -> Vec<Error> {
+    errors.sort_by_key(|(span, _)| span.map(|span| (span.path(), span.line_col())));
+    errors.into_iter().map(|(_, error)| error).collect()
+}
+
+// Several errors, reported together.
+#[derive(Debug)]
+pub struct Errors(Vec<Error>);
+
+impl fmt::Display for Errors {
+    // Prints all the errors, one after the other.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, "\n\nerror: ")?;
+            }
+            write!(f, "{:#}", error)?;
+        }
+        write!(f, "\n\nerror: Could not run the program, {} errors were found.", self.0.len())
+    }
+}
+
+impl std::error::Error for Errors {}
+
+// Walks the AST, collecting errors.
+struct Checker<'a> {
+    ctx: &'a Context,
+    // The variables declared in the function or the system being checked, wherever they are.
+    locals: HashSet<&'static str>,
+    errors: Vec<(Option<Span>, Error)>,
+    warnings: Vec<(Option<Span>, Error)>,
+}
+
+impl Checker<'_> {
+    // Adds the warnings found, and returns the errors.
+    fn report(self, warnings: &mut Vec<Error>) -> Result<()> {
+        warnings.extend(sorted(self.warnings));
+        let mut errors = sorted(self.errors);
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.pop().unwrap()),
+            _ => Err(Errors(errors).into()),
+        }
+    }
+
+    // Records an error, located at the given span.
+    fn error(&mut self, span: Span, error: Error) {
+        let error = Err::<(), _>(error).at(span).unwrap_err();
+        self.errors.push((Some(span), error));
+    }
+
+    // Records a warning, located at the given span.
+    fn warning(&mut self, span: Span, warning: Error) {
+        let warning = Err::<(), _>(warning).at(span).unwrap_err();
+        self.warnings.push((Some(span), warning));
+    }
+
+    // Returns true if the name is the one of a struct, component, resource, event or enum.
+    fn is_type(&self, name: &str) -> bool {
+        matches!(self.ctx.get_def(name), Ok(Def::Component(_) | Def::Resource(_) | Def::Event(_) | Def::Struct(_) | Def::Enum(_)))
+    }
+
+    // Returns the definition of the named enum.
+    fn get_enum(&self, name: &str) -> Result<&'static ast::EnumDef> {
+        match self.ctx.get_def(name) {
+            Ok(Def::Enum(def)) => Ok(def),
+            _ => Err(anyhow!("{} is not an enum.", name)),
+        }
+    }
+
+    // Checks the whole program.
+    fn check_ast(&mut self, ast: &'static ast::AST) {
+        for (name, element) in ast.names.iter() {
+            match element {
+                ast::Name::Component(def) | ast::Name::Resource(def) | ast::Name::Event(def) | ast::Name::Struct(def) => {
+                    self.check_struct_def(def)
+                }
+                ast::Name::Enum(def) => self.check_enum_def(def),
+                ast::Name::Function(fun) => {
+                    // Calls go to the native functions first, so this one would never be called.
+                    if self.ctx.get_native(name).is_some() {
+                        self.error(ast.spans[name], anyhow!("Function {} has the name of a native function, it would never be called.", name));
+                    }
+                    self.locals.clear();
+                    self.check_function(fun, ast.spans[name]);
+                }
+                ast::Name::System(sys) => {
+                    self.locals = sys.filter.resources.iter().chain(&sys.filter.events)
+                        .map(|arg| arg.name)
+                        .collect();
+                    if let Some(filter) = &sys.filter.entities {
+                        self.declare_filter(filter);
+                    }
+                    self.check_filter(&sys.filter, sys.span);
+                    self.check_block(&sys.code);
+                }
+            }
+        }
+
+        for (list, names) in [("Init", &ast.init), ("Run", &ast.run), ("Shutdown", &ast.shutdown)] {
+            for name in names {
+                if !matches!(self.ctx.get_def(name), Ok(Def::System(_))) {
+                    self.errors.push((None, anyhow!("{} in the {} list is not a system.", name, list)));
+                }
+            }
+        }
+    }
+
+    // Checks that the types of the fields exist.
+    fn check_struct_def(&mut self, def: &'static ast::StructDef) {
+        for (field, ty) in def.fields.iter() {
+            match ty {
+                ast::Type::Struct(name) if !self.is_type(name) => {
+                    self.error(def.spans[field], anyhow!("Unknown type {}.", name));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // Checks a function, named or not. The types of its arguments and of its returned value must exist.
+    fn check_function(&mut self, fun: &'static ast::Function, span: Span) {
+        for ty in fun.types.iter().flatten().chain(fun.returns.iter()) {
+            match ty {
+                ast::Type::Struct(name) if !self.is_type(name) => self.error(span, anyhow!("Unknown type {}.", name)),
+                _ => (),
+            }
+        }
+
+        self.locals.extend(fun.args.iter());
+        self.check_block(&fun.body);
+    }
+
+    // Adds the variables bound by an entity filter to the local ones.
+    fn declare_filter(&mut self, filter: &'static ast::EntityFilter) {
+        self.locals.insert(filter.name);
+        self.locals.extend(filter.args.iter().chain(filter.optional.iter()).map(|arg| arg.name));
+    }
+
+    // Checks that the types of the values held by the variants exist.
+    fn check_enum_def(&mut self, def: &'static ast::EnumDef) {
+        for variant in def.variants.iter() {
+            for ty in variant.fields.iter() {
+                match ty {
+                    ast::Type::Struct(name) if !self.is_type(name) => {
+                        self.error(variant.span, anyhow!("Unknown type {}.", name));
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    // Checks that the filter only contains components, resources and events.
+    fn check_filter(&mut self, filter: &'static ast::Filter, span: Span) {
+        if let Some(entities) = &filter.entities {
+            self.check_entity_filter(entities, span);
+        }
+
+        for arg in filter.resources.iter() {
+            if !matches!(self.ctx.get_def(arg.ty), Ok(Def::Resource(_))) {
+                self.error(arg.span, anyhow!("{} is not a resource.", arg.ty));
+            }
+        }
+
+        for arg in filter.events.iter() {
+            if !matches!(self.ctx.get_def(arg.ty), Ok(Def::Event(_))) {
+                self.error(arg.span, anyhow!("{} is not an event.", arg.ty));
+            }
+        }
+    }
+
+    // Checks that the entity filter only contains components.
+    fn check_entity_filter(&mut self, filter: &'static ast::EntityFilter, span: Span) {
+        for arg in filter.args.iter().chain(filter.optional.iter()) {
+            if !matches!(self.ctx.get_def(arg.ty), Ok(Def::Component(_))) {
+                self.error(arg.span, anyhow!("{} is not a component.", arg.ty));
+            }
+        }
+
+        for name in filter.without.iter().chain(filter.added.iter()).chain(filter.changed.iter()) {
+            if !matches!(self.ctx.get_def(name), Ok(Def::Component(_))) {
+                self.error(span, anyhow!("{} is not a component.", name));
+            }
+        }
+    }
+
+    // Checks all statements of a block.
+    fn check_block(&mut self, block: &'static ast::Block) {
+        for stmt in block.statements.iter() {
+            self.check_statement(stmt);
+        }
+    }
+
+    // Checks a statement.
+    fn check_statement(&mut self, stmt: &'static ast::Statement) {
+        match &stmt.kind {
+            ast::StatementKind::Decl(decl) => self.check_decl(decl),
+            ast::StatementKind::If(if_) => {
+                self.check_expr(&if_.cond);
+                self.check_block(&if_.branch1);
+                if let Some(block) = &if_.branch2 {
+                    self.check_block(block);
+                }
+            }
+            ast::StatementKind::For(for_) => {
+                match &for_.init {
+                    Either::Left(expr) => self.check_expr(expr),
+                    Either::Right(decl) => self.check_decl(decl),
+                }
+                self.check_expr(&for_.cond);
+                self.check_expr(&for_.incr);
+                self.check_block(&for_.code);
+            }
+            ast::StatementKind::While(while_) => {
+                self.check_expr(&while_.cond);
+                self.check_block(&while_.code);
+            }
+            ast::StatementKind::Query(query) => {
+                self.declare_filter(&query.filter);
+                self.check_entity_filter(&query.filter, stmt.span);
+                self.check_block(&query.code);
+            }
+            ast::StatementKind::Switch(switch) => {
+                self.check_expr(&switch.expr);
+                for case in switch.cases.iter() {
+                    if let ast::Pattern::Variant(pattern) = &case.pattern {
+                        self.locals.extend(pattern.bindings.iter());
+                        self.check_pattern(pattern, case.span);
+                    }
+                    self.check_block(&case.block);
+                }
+                match &switch.default {
+                    Some(block) => self.check_block(block),
+                    None => self.check_exhaustive(switch, stmt.span),
+                }
+            }
+            ast::StatementKind::Block(block) => self.check_block(block),
+            ast::StatementKind::Expr(expr) | ast::StatementKind::Return(Some(expr)) => self.check_expr(expr),
+            ast::StatementKind::Break | ast::StatementKind::Continue | ast::StatementKind::Return(None) => (),
+        }
+    }
+
+    // Checks that a pattern matches an existing variant, and binds all of its values.
+    fn check_pattern(&mut self, pattern: &'static ast::VariantPattern, span: Span) {
+        let variant = match self.get_enum(pattern.name) {
+            Ok(def) => def.variant(pattern.variant),
+            Err(error) => return self.error(span, error),
+        };
+
+        match variant {
+            None => self.error(span, anyhow!("{} is not a variant of {}.", pattern.variant, pattern.name)),
+            Some(variant) if variant.fields.len() != pattern.bindings.len() => self.error(span, anyhow!(
+                "{}::{} holds {} values, but {} variables were given.", pattern.name, variant.name, variant.fields.len(), pattern.bindings.len()
+            )),
+            _ => (),
+        }
+    }
+
+    // Warns if a switch over an enum, without a default case, doesn't handle all of its variants.
+    fn check_exhaustive(&mut self, switch: &'static ast::Switch, span: Span) {
+        let name = match switch.cases.first().map(|case| &case.pattern) {
+            Some(ast::Pattern::Variant(pattern)) => pattern.name,
+            _ => return,
+        };
+        let def = match self.get_enum(name) {
+            Ok(def) => def,
+            Err(_) => return,
+        };
+
+        let handled = switch.cases.iter()
+            .filter_map(|case| match &case.pattern {
+                ast::Pattern::Variant(pattern) if pattern.name == name => Some(pattern.variant),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        let missing = def.variants.iter()
+            .filter(|variant| !handled.contains(variant.name))
+            .map(|variant| format!("{}::{}", name, variant.name))
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            self.warning(span, anyhow!("This switch over {} has no default case, and does not handle {}.", name, missing.join(", ")));
+        }
+    }
+
+    // Checks a declaration.
+    fn check_decl(&mut self, decl: &'static ast::Decl) {
+        if let Some(expr) = &decl.init {
+            self.check_expr(expr);
+        }
+        self.locals.insert(decl.ident);
+    }
+
+    // Checks an expression and all of its subexpressions.
+    fn check_expr(&mut self, expr: &'static ast::Expr) {
+        match &expr.kind {
+            ast::ExprKind::Assign(assign) => {
+                self.check_lvalue(&assign.lvalue);
+                self.check_expr(&assign.expr);
+            }
+            ast::ExprKind::Ternary(ternary) => {
+                self.check_expr(&ternary.cond);
+                self.check_expr(&ternary.branch1);
+                self.check_expr(&ternary.branch2);
+            }
+            ast::ExprKind::Incr(incr) => self.check_lvalue(&incr.lvalue),
+            ast::ExprKind::Atom(_) => (),
+            ast::ExprKind::LValue(lvalue) => self.check_lvalue(lvalue),
+            ast::ExprKind::ListInit(list_init) => {
+                for expr in list_init.exprs.iter() {
+                    self.check_expr(expr);
+                }
+            }
+            ast::ExprKind::StructInit(struct_init) => self.check_struct_init(struct_init, expr.span),
+            ast::ExprKind::EnumInit(enum_init) => self.check_enum_init(enum_init, expr.span),
+            ast::ExprKind::Call(call) => self.check_call(call),
+            ast::ExprKind::Function(fun) => self.check_function(fun, expr.span),
+            ast::ExprKind::BinExpr(bin_expr) => {
+                self.check_expr(&bin_expr.left);
+                self.check_expr(&bin_expr.right);
+            }
+            ast::ExprKind::UnExpr(un_expr) => self.check_expr(&un_expr.expr),
+        }
+    }
+
+    // Checks the indices of a left value.
+    fn check_lvalue(&mut self, lvalue: &'static ast::LValue) {
+        let indices = std::iter::once(&lvalue.first_index).chain(lvalue.path.iter().map(|(_, index)| index));
+        for expr in indices.flat_map(|index| index.exprs.iter()) {
+            self.check_expr(expr);
+        }
+    }
+
+    // Checks that a struct initializer gives a value of the right type to each field.
+    fn check_struct_init(&mut self, struct_init: &'static ast::StructInit, span: Span) {
+        for (_, expr) in struct_init.fields.iter() {
+            self.check_expr(expr);
+        }
+
+        let def = match self.ctx.get_def(struct_init.name) {
+            Ok(Def::Component(def) | Def::Resource(def) | Def::Event(def) | Def::Struct(def)) => def,
+            _ => return self.error(span, anyhow!("{} is not a struct type.", struct_init.name)),
+        };
+
+        let mut initialized = HashSet::new();
+        for (name, expr) in struct_init.fields.iter() {
+            let ty = match def.fields.get(name) {
+                Some(ty) => ty,
+                None => {
+                    self.error(span, anyhow!("{} is not a field of {}.", name, struct_init.name));
+                    continue;
+                }
+            };
+
+            if !initialized.insert(*name) {
+                self.error(expr.span, anyhow!("{} is already initialized.", name));
+            }
+
+            match type_of(expr) {
+                Some(given) if given != *ty => self.error(expr.span, anyhow!(
+                    "Field {} of {} should be of type {}, but a value of type {} was given.", name, struct_init.name, ty, given
+                )),
+                _ => (),
+            }
+        }
+
+        let mut missing = def.fields.keys().filter(|name| !initialized.contains(*name)).collect::<Vec<_>>();
+        missing.sort();
+        for name in missing {
+            self.error(span, anyhow!("Field {} of {} is not initialized.", name, struct_init.name));
+        }
+    }
+
+    // Checks that an enum value is of an existing variant, and holds values of the right types.
+    fn check_enum_init(&mut self, enum_init: &'static ast::EnumInit, span: Span) {
+        for expr in enum_init.args.iter() {
+            self.check_expr(expr);
+        }
+
+        let variant = match self.get_enum(enum_init.name) {
+            Ok(def) => match def.variant(enum_init.variant) {
+                Some(variant) => variant,
+                None => return self.error(span, anyhow!("{} is not a variant of {}.", enum_init.variant, enum_init.name)),
+            },
+            Err(error) => return self.error(span, error),
+        };
+
+        if variant.fields.len() != enum_init.args.len() {
+            return self.error(span, anyhow!(
+                "{}::{} holds {} values, but {} were given.", enum_init.name, variant.name, variant.fields.len(), enum_init.args.len()
+            ));
+        }
+
+        for (ty, expr) in variant.fields.iter().zip(enum_init.args.iter()) {
+            match type_of(expr) {
+                Some(given) if given != *ty => self.error(expr.span, anyhow!(
+                    "{}::{} should hold a value of type {}, but a value of type {} was given.", enum_init.name, variant.name, ty, given
+                )),
+                _ => (),
+            }
+        }
+    }
+
+    // Checks the number of arguments of a call, and the types of the ones given literally.
+    fn check_call(&mut self, call: &'static ast::Call) {
+        for expr in call.args.iter() {
+            self.check_expr(expr);
+        }
+
+        // Native function, builtin or registered by the host.
+        if let Some(native) = self.ctx.get_native(call.name) {
+            let params = match &native.params {
+                Some(params) => params,
+                None => return,
+            };
+
+            if params.len() != call.args.len() {
+                return self.error(call.span, anyhow!(
+                    "{} expected exactly {} arguments, but {} where provided", call.name, params.len(), call.args.len()
+                ));
+            }
+
+            return self.check_args(call, params);
+        }
+
+        // User-defined function.
+        match self.ctx.get_def(call.name) {
+            Ok(Def::Function(def)) if def.args.len() != call.args.len() => self.error(call.span, anyhow!(
+                "{} expected exactly {} arguments, but {} where provided", call.name, def.args.len(), call.args.len()
+            )),
+            Ok(Def::Function(def)) => self.check_args(call, &def.types),
+            Ok(_) => self.error(call.span, anyhow!("{} is not a function.", call.name)),
+            // A variable holding a function.
+            Err(_) if self.locals.contains(call.name) => (),
+            Err(_) => self.error(call.span, anyhow!("Function {} does not exist.", call.name)),
+        }
+    }
+
+    // Checks the types of the arguments of a call against the declared ones, when they are known.
+    fn check_args(&mut self, call: &'static ast::Call, params: &[Option<ast::Type>]) {
+        for (i, (param, expr)) in params.iter().zip(call.args.iter()).enumerate() {
+            match (param, type_of(expr)) {
+                (Some(ty), Some(given)) if *ty != given => self.error(expr.span, anyhow!(
+                    "Argument {} of {} should be of type {}, but a value of type {} was provided.", i + 1, call.name, ty, given
+                )),
+                _ => (),
+            }
+        }
+    }
+}
+
+// Returns the type of an expression, if it is known without running the program.
+fn type_of(expr: &'static ast::Expr) -> Option<ast::Type> {
+    use ast::BinOp::*;
+    use ast::Type::*;
+
+    match &expr.kind {
+        ast::ExprKind::Atom(atom) => Some(match atom {
+            ast::Atom::Void => Void,
+            ast::Atom::Bool(_) => Bool,
+            ast::Atom::Int(_) => Int,
+            ast::Atom::Float(_) => Float,
+            ast::Atom::Char(_) => Char,
+            ast::Atom::String(_) => String,
+        }),
+        ast::ExprKind::ListInit(_) => Some(List),
+        ast::ExprKind::Function(_) => Some(Function),
+        ast::ExprKind::StructInit(struct_init) => Some(Struct(struct_init.name)),
+        ast::ExprKind::EnumInit(enum_init) => Some(Struct(enum_init.name)),
+        ast::ExprKind::Assign(assign) => match assign.op {
+            Some(_) => None,
+            None => type_of(&assign.expr),
+        },
+        ast::ExprKind::Ternary(ternary) => match (type_of(&ternary.branch1), type_of(&ternary.branch2)) {
+            (Some(ty1), Some(ty2)) if ty1 == ty2 => Some(ty1),
+            _ => None,
+        },
+        ast::ExprKind::BinExpr(bin_expr) => match (type_of(&bin_expr.left), bin_expr.op, type_of(&bin_expr.right)) {
+            (_, Leq | Geq | Lt | Gt | Eq | Neq, _) => Some(Bool),
+            (Some(String), Add, _) | (_, Add, Some(String)) | (Some(Char), Add, Some(Char)) => Some(String),
+            (Some(String), Mul, Some(Int)) | (Some(Int), Mul, Some(String)) => Some(String),
+            (Some(Int), Add | Sub | Mul | Div | Mod | BitAnd | BitOr | Xor | Shl | Shr, Some(Int)) => Some(Int),
+            (Some(Int | Float), Add | Sub | Mul | Div, Some(Int | Float)) => Some(Float),
+            (Some(Bool), And | Or | Xor, Some(Bool)) => Some(Bool),
+            _ => None,
+        },
+        ast::ExprKind::UnExpr(un_expr) => match (un_expr.op, type_of(&un_expr.expr)) {
+            (ast::UnOp::Not, Some(Bool)) => Some(Bool),
+            (ast::UnOp::BitNot, Some(Int)) => Some(Int),
+            (ast::UnOp::Pos | ast::UnOp::Neg, Some(ty @ (Int | Float))) => Some(ty),
+            _ => None,
+        },
+        ast::ExprKind::Incr(_) | ast::ExprKind::LValue(_) | ast::ExprKind::Call(_) => None,
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::{self, Write};
+
+use ast::Type;
+
+use super::*;
+
+// Gets a list from a variable.
+fn get_list(var: &Var) -> Result<&Shared<Vec<Var>>> {
+    match var {
+        Var::List(list) => Ok(list),
+        var => Err(anyhow!("Expected a list, but {} was provided.", var)),
+    }
+}
+
+// Prints all the given variables.
+fn print_all(args: &[Var]) {
+    for var in args {
+        print!("{}", var);
+    }
+}
+
+// Returns the native functions of the standard library.
+pub fn builtins() -> Natives {
+    let mut natives = Natives::new();
+    let mut add = |name: &str, native| natives.insert(name.to_string(), native);
+
+    // List manipulation.
+    add("append", Native::new(&[Some(Type::List), Some(Type::List)], |_, args| {
+        let list2 = get_list(&args[1])?.borrow_mut().split_off(0);
+        get_list(&args[0])?.borrow_mut().extend(list2);
+        Ok(Var::Void)
+    }));
+    add("len", Native::new(&[None], |_, args| match &args[0] {
+        Var::String(s) => Ok(Var::Int(s.len() as i64)),
+        Var::List(list) => Ok(Var::Int(list.borrow().len() as i64)),
+        var => Err(anyhow!("Expected a list, but {} was provided.", var)),
+    }));
+    add("pop", Native::new(&[Some(Type::List)], |_, args| {
+        let pop = get_list(&args[0])?.borrow_mut().pop();
+        pop.ok_or_else(|| anyhow!("List is empty."))
+    }));
+    add("push", Native::new(&[Some(Type::List), None], |_, mut args| {
+        let val = args.pop().unwrap();
+        get_list(&args[0])?.borrow_mut().push(val);
+        Ok(Var::Void)
+    }));
+    add("remove", Native::new(&[Some(Type::List), Some(Type::Int)], |_, args| {
+        let mut borrow = get_list(&args[0])?.borrow_mut();
+        let i = match args[1] {
+            Var::Int(i) => i as usize,
+            _ => unreachable!(),
+        };
+        if i >= borrow.len() {
+            return Err(anyhow!("Index {} is out of bounds.", i));
+        }
+        Ok(borrow.remove(i))
+    }));
+
+    // Type conversions.
+    add("bool", Native::new(&[None], |_, args| match &args[0] {
+        Var::Void => Ok(Var::Bool(false)),
+        Var::Bool(b) => Ok(Var::Bool(*b)),
+        Var::Int(i) => Ok(Var::Bool(*i != 0)),
+        Var::String(s) => Ok(Var::Bool(s.parse::<bool>()?)),
+        var => Err(anyhow!("Cannot convert {} to an int.", var)),
+    }));
+    add("int", Native::new(&[None], |_, args| match &args[0] {
+        Var::Void => Ok(Var::Int(0)),
+        Var::Bool(b) => Ok(Var::Int(*b as i64)),
+        Var::Int(i) => Ok(Var::Int(*i)),
+        Var::Float(f) => Ok(Var::Int(*f as i64)),
+        Var::Char(c) => Ok(Var::Int(*c as i64)),
+        Var::String(s) => Ok(Var::Int(s.parse::<i64>()?)),
+        var => Err(anyhow!("Cannot convert {} to an int.", var)),
+    }));
+    add("float", Native::new(&[None], |_, args| match &args[0] {
+        Var::Void => Ok(Var::Float(0.0)),
+        Var::Int(i) => Ok(Var::Float(*i as f64)),
+        Var::Float(f) => Ok(Var::Float(*f)),
+        Var::String(s) => Ok(Var::Float(s.parse::<f64>()?)),
+        var => Err(anyhow!("Cannot convert {} to an int.", var)),
+    }));
+    add("char", Native::new(&[None], |_, args| match &args[0] {
+        Var::Int(i) => Ok(Var::Char(char::from_u32(*i as u32).ok_or_else(|| anyhow!("Invalid unicode code point {}.", i))?)),
+        Var::Char(c) => Ok(Var::Char(*c)),
+        Var::String(s) => {
+            let mut chars = s.chars();
+            let c = chars.next().ok_or_else(|| anyhow!("String is empty."))?;
+            match chars.next() {
+                None => Ok(Var::Char(c)),
+                Some(_) => Err(anyhow!("String contains more than one character.")),
+            }
+        }
+        var => Err(anyhow!("Cannot convert {} to a char.", var)),
+    }));
+    add("string", Native::new(&[None], |_, args| {
+        Ok(Var::String(args[0].to_string()))
+    }));
+
+    // User input.
+    add("input", Native::variadic(|_, args| {
+        print_all(&args);
+        let mut input = String::new();
+        io::stdout().flush()?;
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Err(anyhow!("Reached the end of the input."));
+        }
+        Ok(Var::String(input.trim().to_string()))
+    }));
+
+    // Displaying.
+    add("print", Native::variadic(|_, args| {
+        print_all(&args);
+        Ok(Var::Void)
+    }));
+    add("println", Native::variadic(|_, args| {
+        print_all(&args);
+        println!();
+        Ok(Var::Void)
+    }));
+
+    // Program flow.
+    add("Exit", Native::new(&[Some(Type::Int)], |ctx, args| {
+        match args[0] {
+            Var::Int(code) => ctx.exit(code as i32),
+            _ => unreachable!(),
+        }
+        Ok(Var::Void)
+    }));
+
+    // ECS related.
+    add("Spawn", Native::variadic(|ctx, args| {
+        let entity = ctx.world_mut().reserve_entity();
+        ctx.new_command(Command::SpawnEntity(entity.clone(), args));
+        Ok(Var::Entity(entity))
+    }));
+    add("Delete", Native::new(&[Some(Type::Entity)], |ctx, mut args| {
+        ctx.new_command(Command::DeleteEntity(args.pop().unwrap(), ctx.deletion()));
+        Ok(Var::Void)
+    }));
+    add("Insert", Native::variadic(|ctx, mut args| {
+        if !matches!(args.first(), Some(Var::Entity(_))) {
+            return Err(anyhow!("Insert expects an entity followed by components."));
+        }
+        let components = args.split_off(1);
+        ctx.new_command(Command::InsertComponents(args.pop().unwrap(), components));
+        Ok(Var::Void)
+    }));
+    add("RemoveComponent", Native::new(&[Some(Type::Entity), Some(Type::String)], |ctx, mut args| {
+        let name = args.pop().unwrap().to_string();
+        ctx.new_command(Command::RemoveComponent(args.pop().unwrap(), name));
+        Ok(Var::Void)
+    }));
+    add("IsAlive", Native::new(&[Some(Type::Entity)], |ctx, args| {
+        Ok(Var::Bool(ctx.world().is_alive(&get_entity(&args[0])?)))
+    }));
+    add("Get", Native::new(&[Some(Type::Entity), Some(Type::String)], |ctx, args| {
+        ctx.world().get_component(get_entity(&args[0])?, &args[1].to_string())
+    }));
+    add("TryGet", Native::new(&[Some(Type::Entity), Some(Type::String)], |ctx, args| {
+        ctx.world().get_optional_component(get_entity(&args[0])?, &args[1].to_string())
+    }));
+    add("Has", Native::new(&[Some(Type::Entity), Some(Type::String)], |ctx, args| {
+        Ok(Var::Bool(ctx.world().has_component(get_entity(&args[0])?, &args[1].to_string())?))
+    }));
+    add("Send", Native::new(&[None], |ctx, mut args| {
+        let event = args.pop().unwrap();
+        let name = event.struct_type()?;
+        if !ctx.world().is_event(name) {
+            return Err(anyhow!("{} is not an event.", name));
+        }
+        ctx.new_command(Command::SendEvent(event));
+        Ok(Var::Void)
+    }));
+    add("NewResource", Native::new(&[None], |ctx, mut args| {
+        ctx.new_command(Command::NewResource(args.pop().unwrap()));
+        Ok(Var::Void)
+    }));
+    add("SetResource", Native::new(&[None], |ctx, mut args| {
+        ctx.new_command(Command::SetResource(args.pop().unwrap()));
+        Ok(Var::Void)
+    }));
+    add("DeleteResource", Native::new(&[Some(Type::String)], |ctx, args| {
+        ctx.new_command(Command::DeleteResource(args[0].to_string()));
+        Ok(Var::Void)
+    }));
+    add("HasResource", Native::new(&[Some(Type::String)], |ctx, args| {
+        Ok(Var::Bool(ctx.world().has_resource(&args[0].to_string())?))
+    }));
+
+    // Snapshots of the world.
+    add("Save", Native::new(&[Some(Type::String)], |ctx, args| {
+        ctx.world().save(&args[0].to_string())?;
+        Ok(Var::Void)
+    }));
+    add("Load", Native::new(&[Some(Type::String)], |ctx, args| {
+        let snapshot = ctx.world().load(&args[0].to_string())?;
+        ctx.new_command(Command::Load(snapshot));
+        Ok(Var::Void)
+    }));
+
+    natives
+}
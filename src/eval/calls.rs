@@ -1,178 +1,120 @@
-use std::io::{self, Write};
+use std::rc::Rc;
 
 use super::*;
 
-// Gets a list from an expression.
-fn get_list(ctx: &Context, scope: &Scope, expr: &'static ast::Expr) -> Result<Shared<Vec<Var>>> {
-    match eval_expr(ctx, scope, expr)? {
-        Var::List(list) => Ok(list),
-        var => return Err(anyhow!("Expected a list, but {} was provided.", var)),
+// Evaluates a call expression.
+pub fn eval_call(ctx: &Context, scope: &Scope, call: &'static ast::Call) -> Result<Var> {
+    eval_call_inner(ctx, scope, call).at(call.span)
+}
+
+// Evaluates a call expression, without locating the errors.
+fn eval_call_inner(ctx: &Context, scope: &Scope, call: &'static ast::Call) -> Result<Var> {
+    let ast::Call {name, args, ..} = call;
+
+    // Native function, builtin or registered by the host.
+    if let Some(native) = ctx.get_native(name) {
+        let args = args.iter().map(|expr| eval_expr(ctx, scope, expr)).collect::<Result<_>>()?;
+        return native.call(ctx, name, args);
+    }
+
+    // User-defined function, or else a variable holding a function.
+    let def = match ctx.get_def(name) {
+        Ok(Def::Function(def)) => def,
+        Ok(_) => return Err(anyhow!("{} is not a function.", name)),
+        Err(err) => return match scope.get_var(name) {
+            Ok(var) => {
+                let args = args.iter().map(|expr| eval_expr(ctx, scope, expr)).collect::<Result<_>>()?;
+                call_value(ctx, name, &var, args)
+            }
+            Err(_) => Err(err),
+        },
+    };
+
+    if args.len() != def.args.len() {
+        return Err(anyhow!("{} expected exactly {} arguments, but {} where provided", name, def.args.len(), args.len()));
     }
+
+    let func_scope = Scope::default();
+    for (i, (arg_name, arg)) in def.args.iter().zip(args).enumerate() {
+        let var = eval_expr(ctx, scope, arg)?;
+        check_arg(name, i, &def.types[i], &var).at(arg.span)?;
+        func_scope.new_var(arg_name, var);
+    }
+    func_scope.next();
+
+    eval_body(ctx, &func_scope, name, def)
 }
 
-// Gets a list from an expression.
-fn get_int(ctx: &Context, scope: &Scope, expr: &'static ast::Expr) -> Result<i64> {
-    match eval_expr(ctx, scope, expr)? {
-        Var::Int(i) => Ok(i),
-        var => return Err(anyhow!("Expected an integer, but {} was provided.", var)),
+// Calls a function value with arguments already evaluated. The captured variables
+// come first, for the arguments to shadow them.
+fn call_value(ctx: &Context, name: &str, var: &Var, args: Vec<Var>) -> Result<Var> {
+    let closure = as_function(name, var)?;
+    check_args(name, closure.def, &args)?;
+
+    let func_scope = Scope::default();
+    for (capture, var) in closure.captures.iter() {
+        func_scope.new_var(capture, var.clone());
+    }
+    for (arg_name, var) in closure.def.args.iter().zip(args) {
+        func_scope.new_var(arg_name, var);
     }
+    func_scope.next();
+
+    eval_body(ctx, &func_scope, name, closure.def)
 }
 
-// Evaluates a call expression.
-pub fn eval_call(ctx: &Context, scope: &Scope, call: &'static ast::Call) -> Result<Var> {
-    let ast::Call {name, args} = call;
-
-    let check_args = |n| (args.len() == n)
-        .then(|| ())
-        .ok_or_else(|| anyhow!("{} expected exactly {} arguments, but {} where provided", name, n, args.len()));
-
-    match *name {
-        // List manipulation.
-        "append" => {
-            check_args(2)?;
-            let list1 = get_list(ctx, scope, &args[0])?;
-            let list2 = get_list(ctx, scope, &args[1])?;
-            list1.borrow_mut().append(&mut list2.borrow_mut());
-        }
-        "len" => {
-            check_args(1)?;
-            return match eval_expr(ctx, scope, &args[0])? {
-                Var::String(s) => Ok(Var::Int(s.len() as i64)),
-                Var::List(list) => Ok(Var::Int(list.borrow().len() as i64)),
-                var =>  Err(anyhow!("Expected a list, but {} was provided.", var)),
-            };
-        }
-        "pop" => {
-            check_args(1)?;
-            let pop = get_list(ctx, scope, &args[0])?.borrow_mut().pop();
-            return Ok(pop.ok_or_else(|| anyhow!("List is empty."))?);
-        }
-        "push" => {
-            check_args(2)?;
-            let list = get_list(ctx, scope, &args[0])?;
-            let val = eval_expr(ctx, scope, &args[1])?;
-            list.borrow_mut().push(val);
-        }
-        "remove" => {
-            check_args(2)?;
-            let list = get_list(ctx, scope, &args[0])?;
-            let i = get_int(ctx, scope, &args[1])? as usize;
-            let mut borrow = list.borrow_mut();
-            if i >= borrow.len() {
-                return Err(anyhow!("Index {} is out of bounds.", i));
-            }
-            return Ok(borrow.remove(i as usize));
-        }
-        // Type conversions.
-        "bool" => {
-            check_args(1)?;
-            return match eval_expr(ctx, scope, &args[0])? {
-                Var::Void => Ok(Var::Bool(false)),
-                Var::Bool(b) => Ok(Var::Bool(b)),
-                Var::Int(i) => Ok(Var::Bool(i != 0)),
-                Var::String(s) => Ok(Var::Bool(s.parse::<bool>()?)),
-                var => Err(anyhow!("Cannot convert {} to an int.", var)),
-            };
-        }
-        "int" => {
-            check_args(1)?;
-            return match eval_expr(ctx, scope, &args[0])? {
-                Var::Void => Ok(Var::Int(0)),
-                Var::Bool(b) => Ok(Var::Int(b as i64)),
-                Var::Int(i) => Ok(Var::Int(i)),
-                Var::Float(f) => Ok(Var::Int(f as i64)),
-                Var::Char(c) => Ok(Var::Int(c as i64)),
-                Var::String(s) => Ok(Var::Int(s.parse::<i64>()?)),
-                var => Err(anyhow!("Cannot convert {} to an int.", var)),
-            };
-        }
-        "float" => {
-            check_args(1)?;
-            return match eval_expr(ctx, scope, &args[0])? {
-                Var::Void => Ok(Var::Float(0.0)),
-                Var::Int(i) => Ok(Var::Float(i as f64)),
-                Var::Float(f) => Ok(Var::Float(f)),
-                Var::String(s) => Ok(Var::Float(s.parse::<f64>()?)),
-                var => Err(anyhow!("Cannot convert {} to an int.", var)),
-            };
-        }
-        "char" => {
-            check_args(1)?;
-            return match eval_expr(ctx, scope, &args[0])? {
-                Var::Int(i) => Ok(Var::Char(char::from_u32(i as u32).ok_or_else(|| anyhow!("Invalid unicode code point {}.", i))?)),
-                Var::Char(c) => Ok(Var::Char(c)),
-                Var::String(s) => {
-                    let chars = s.chars();
-                    let c = s.chars().next().ok_or_else(|| anyhow!("String is empty."))?;
-                    s.chars().next().is_none()
-                        .then(|| Ok(Var::Char(c)))
-                        .unwrap_or_else(|| Err(anyhow!("String contains more than one character.")))
-                }
-                var => Err(anyhow!("Cannot convert {} to a char.", var)),
-            };
-        }
-        "string" => {
-            check_args(1)?;
-            return Ok(Var::String(eval_expr(ctx, scope, &args[0])?.to_string()));
-        }
-        // User input.
-        "input" => {
-            for expr in args {
-                print!("{}", eval_expr(ctx, scope, expr)?);
-            }
-            let mut input = String::new();
-            io::stdout().flush();
-            io::stdin().read_line(&mut input).unwrap();
-            return Ok(Var::String(input.trim().to_string()));
-        }
-        // Displaying.
-        "print" => {
-            for expr in args {
-                print!("{}", eval_expr(ctx, scope, expr)?);
-            }
-        }
-        "println" => {
-            for expr in args {
-                print!("{}", eval_expr(ctx, scope, expr)?);
-            }
-            println!();
-        }
-        // ECS related.
-        "Spawn" => {
-            let components = args.iter().map(|expr| eval_expr(ctx, scope, expr)).collect::<Result<_>>()?;
-            ctx.new_command(Command::SpawnEntity(components));
-        }
-        "Delete" => {
-            check_args(1)?;
-            ctx.new_command(Command::DeleteEntity(eval_expr(ctx, scope, &args[0])?));
-        }
-        "NewResource" => {
-            check_args(1)?;
-            ctx.new_command(Command::NewResource(eval_expr(ctx, scope, &args[0])?));
-        }
-        // User-defined function.
-        _ => {
-            let def = match ctx.get_def(name)? {
-                Def::Function(def) => def,
-                _ => return Err(anyhow!("{} is not a function.", name)),
-            };
-
-            check_args(def.args.len())?;
-        
-            let func_scope = Scope::default();
-            for (name, arg) in def.args.iter().zip(args) {
-                func_scope.new_var(name, eval_expr(ctx, scope, arg)?);
-            }
-            func_scope.next();
-        
-            return match eval_block(ctx, &func_scope, &def.body)? {
-                Flow::Return(val) => Ok(val),
-                Flow::Break => Err(anyhow!("Cannot break outside of a loop.")),
-                Flow::Continue => Err(anyhow!("Cannot continue outside of a loop.")),
-                _ => Ok(Var::Void),
-            };
-        }
+// Evaluates the body of a function, once its arguments are in scope.
+fn eval_body(ctx: &Context, scope: &Scope, name: &str, def: &'static ast::Function) -> Result<Var> {
+    let val = match eval_block(ctx, scope, &def.body)? {
+        Flow::Return(val) => val,
+        Flow::Break => return Err(anyhow!("Cannot break outside of a loop.")),
+        Flow::Continue => return Err(anyhow!("Cannot continue outside of a loop.")),
+        _ => Var::Void,
+    };
+    check_return(name, &def.returns, &val)?;
+    Ok(val)
+}
+
+// Returns the value of a named function.
+pub fn function_value(name: &'static str, def: &'static ast::Function) -> Var {
+    Var::Function(Rc::new(Closure {name: Some(name), def, captures: Vec::new()}))
+}
+
+// Gets the function held by a variable called like a function.
+pub fn as_function<'a>(name: &str, var: &'a Var) -> Result<&'a Rc<Closure>> {
+    match var {
+        Var::Function(closure) => Ok(closure),
+        _ => Err(anyhow!("{} is not a function.", name)),
+    }
+}
+
+// Checks the number and the types of the arguments given to a function value.
+pub fn check_args(name: &str, def: &ast::Function, args: &[Var]) -> Result<()> {
+    if args.len() != def.args.len() {
+        return Err(anyhow!("{} expected exactly {} arguments, but {} where provided", name, def.args.len(), args.len()));
+    }
+    for (i, (ty, var)) in def.types.iter().zip(args).enumerate() {
+        check_arg(name, i, ty, var)?;
     }
+    Ok(())
+}
 
-    Ok(Var::Void)
-}
\ No newline at end of file
+// Checks the type of the argument with the given index given to a function, if it declares one.
+pub fn check_arg(name: &str, i: usize, ty: &Option<ast::Type>, var: &Var) -> Result<()> {
+    match ty {
+        Some(ty) if var.get_type() != *ty => Err(anyhow!(
+            "Argument {} of {} should be of type {}, but {} was provided.", i + 1, name, ty, var
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Checks the type of the value returned by a function, if it declares one.
+pub fn check_return(name: &str, ty: &Option<ast::Type>, var: &Var) -> Result<()> {
+    match ty {
+        Some(ty) if var.get_type() != *ty => Err(anyhow!(
+            "{} should return a value of type {}, but {} was returned.", name, ty, var
+        )),
+        _ => Ok(()),
+    }
+}
@@ -1,4 +1,4 @@
-use std::cell::{RefCell, Ref, RefMut};
+use std::cell::{Cell, RefCell, Ref, RefMut};
 use std::mem;
 
 use super::*;
@@ -20,6 +20,16 @@ impl Scope {
         self.vars.borrow_mut().pop();
     }
 
+    // Returns the number of scope levels.
+    pub fn depth(&self) -> usize {
+        self.vars.borrow().len()
+    }
+
+    // Destroys the scope levels created after the given number of levels, as left by an error.
+    pub fn truncate(&self, depth: usize) {
+        self.vars.borrow_mut().truncate(depth);
+    }
+
     // Adds a new variable to the topmost scope.
     pub fn new_var(&self, name: &'static str, val: Var) {
         self.vars.borrow_mut().last_mut().unwrap().insert(name, val);
@@ -40,13 +50,27 @@ impl Scope {
             .find_map(|ctx| ctx.get_mut(name))
             .ok_or_else(|| anyhow!("Variable {} does not exist in current ctx.", name))?;
 
-        if matches!(var, Var::Struct(_) | Var::List(_)) {
-            return Err(anyhow!("Cannot reassign to a struct or list variable in a ctx."));
-        }
-
+        check_reassign(var)?;
         *var = val;
         Ok(())
     }
+
+    // Returns the names of all the variables.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.vars.borrow().iter().flat_map(|level| level.keys().copied()).collect()
+    }
+
+    // Copies all the variables in scope, sorted by name, for an anonymous function to capture them.
+    pub fn captures(&self) -> Vec<(&'static str, Var)> {
+        let mut vars = Map::new();
+        for level in self.vars.borrow().iter() {
+            vars.extend(level.iter().map(|(name, var)| (*name, var.clone())));
+        }
+
+        let mut captures = vars.into_iter().collect::<Vec<_>>();
+        captures.sort_by_key(|(name, _)| *name);
+        captures
+    }
 }
 
 impl Default for Scope {
@@ -62,8 +86,15 @@ impl Default for Scope {
 #[derive(Debug)]
 pub struct Context {
     defs: &'static Map<Def>,
+    natives: Natives,
     world: RefCell<World>,
     commands: RefCell<Vec<Command>>,
+    // The exit code requested by a script, if any.
+    exit: Cell<Option<i32>>,
+    // The system running, if any.
+    system: Cell<Option<&'static str>>,
+    // The number of times the Run systems were started, 0 while the Init systems run.
+    step: Cell<u64>,
 }
 
 impl Context {
@@ -71,8 +102,12 @@ impl Context {
     pub fn new(defs: &'static Map<Def>) -> Context {
         Context {
             defs,
+            natives: builtins(),
             world: RefCell::new(World::new(defs)),
             commands: RefCell::new(vec![]),
+            exit: Cell::new(None),
+            system: Cell::new(None),
+            step: Cell::new(0),
         }
     }
 
@@ -82,23 +117,98 @@ impl Context {
         self.defs.get(name).cloned().ok_or_else(|| anyhow!("Definition {} does not exist", name))
     }
 
+    // Returns the definition corresponding to the given name, along with the
+    // static version of that name.
+    pub fn get_def_entry(&self, name: &str) -> Result<(&'static str, Def)> {
+        self.defs.get_key_value(name)
+            .map(|(name, def)| (*name, def.clone()))
+            .ok_or_else(|| anyhow!("Definition {} does not exist", name))
+    }
+
+    // Returns all the definitions.
+    pub fn defs(&self) -> &'static Map<Def> {
+        self.defs
+    }
+
+    // Replaces the definitions, keeping the world. Used to add names to a running program.
+    pub fn set_defs(&mut self, defs: &'static Map<Def>) {
+        self.defs = defs;
+        self.world.get_mut().set_defs(defs);
+    }
+
+    // Returns the native function with the given name, if there is one.
+    pub fn get_native(&self, name: &str) -> Option<&Native> {
+        self.natives.get(name)
+    }
+
+    // Registers a new native function, replacing any previous one with the same name.
+    pub fn register_native(&mut self, name: &str, native: Native) {
+        self.natives.insert(name.to_string(), native);
+    }
+
     // Updates the worlds with the latest commands.
     pub fn update(&self) -> Result<()> {
         self.world.borrow_mut().do_commands(&mut self.commands.borrow_mut())
     }
 
+    // Drops the commands not done yet, as the code that gave them failed.
+    pub fn discard_commands(&self) {
+        let commands = std::mem::take(&mut *self.commands.borrow_mut());
+        self.world.borrow_mut().discard_commands(commands);
+    }
+
     // Adds a new command to the context.
     pub fn new_command(&self, cmd: Command) {
         self.commands.borrow_mut().push(cmd);
     }
 
+    // Requests the program to exit with the given code. Only the first request is kept.
+    pub fn exit(&self, code: i32) {
+        if self.exit.get().is_none() {
+            self.exit.set(Some(code));
+        }
+    }
+
+    // Returns the exit code, if the program was requested to exit.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit.get()
+    }
+
+    // Runs the given function as the named system, advancing the tick of the world.
+    pub fn in_system<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let outer = self.system.replace(Some(name));
+        self.world.borrow_mut().start_system(name);
+        let result = f();
+        self.system.set(outer);
+        result
+    }
+
+    // Counts a new start of the Run systems, dropping the events sent before the previous one.
+    pub fn next_step(&self) {
+        self.step.set(self.step.get() + 1);
+        self.world.borrow_mut().clear_events();
+    }
+
+    // Returns the events of the given type sent since the running system last read them.
+    pub fn read_events(&self, name: &'static str) -> Var {
+        self.world.borrow_mut().read_events(self.system.get().unwrap_or_default(), name)
+    }
+
+    // Returns the deletion of an entity happening now.
+    pub fn deletion(&self) -> Deletion {
+        Deletion {
+            system: self.system.get(),
+            step: self.step.get(),
+        }
+    }
+
     // Gets an immutable reference to the world.
-    pub fn world(&self) -> Ref<World> {
+    pub fn world(&self) -> Ref<'_, World> {
         self.world.borrow()
     }
  
     // Gets a mutable reference to the world.
-    pub fn world_mut(&self) -> RefMut<World> {
+    pub fn world_mut(&self) -> RefMut<'_, World> {
         self.world.borrow_mut()
     }
 }
@@ -110,7 +220,9 @@ pub enum Def {
     System(&'static ast::System),
     Component(&'static ast::StructDef),
     Resource(&'static ast::StructDef),
+    Event(&'static ast::StructDef),
     Struct(&'static ast::StructDef),
+    Enum(&'static ast::EnumDef),
 }
 
 // The result of the evaluation of a statement.
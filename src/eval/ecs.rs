@@ -1,6 +1,8 @@
-use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::rc::Rc;
 
 use crate::ast::EntityFilter;
 
@@ -11,9 +13,15 @@ use super::*;
 // A command to be executed by the world.
 #[derive(Debug)]
 pub enum Command {
-    SpawnEntity(Vec<Var>),
-    DeleteEntity(Var),
+    SpawnEntity(Entity, Vec<Var>),
+    DeleteEntity(Var, Deletion),
+    InsertComponents(Var, Vec<Var>),
+    RemoveComponent(Var, String),
     NewResource(Var),
+    SetResource(Var),
+    DeleteResource(String),
+    SendEvent(Var),
+    Load(Snapshot),
 }
 
 // =============================================================== EntityFilter impl
@@ -28,7 +36,7 @@ impl Hash for ast::EntityFilter {
 // For EntityFilter, compares are just the references compared as integers.
 impl PartialEq for ast::EntityFilter {
     fn eq(&self, other: &Self) -> bool {
-        self as *const Self == other as *const Self
+        std::ptr::eq(self, other)
     }
 }
 
@@ -37,25 +45,65 @@ impl Eq for ast::EntityFilter {}
 
 impl fmt::Display for EntityFilter {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(arg) = self.args.first() {
-            write!(f, "{}", arg.ty)?;
-        }
-        for arg in &self.args[1..] {
-            write!(f, ", {}", arg.ty)?;
-        }
-        Ok(())
+        let terms = self.args.iter().map(|arg| arg.ty.to_string())
+            .chain(self.without.iter().map(|ty| format!("!{}", ty)))
+            .chain(self.optional.iter().map(|arg| format!("?{}", arg.ty)))
+            .chain(self.added.iter().map(|ty| format!("Added<{}>", ty)))
+            .chain(self.changed.iter().map(|ty| format!("Changed<{}>", ty)))
+            .collect::<Vec<_>>();
+        write!(f, "{}", terms.join(", "))
     }
 }
 
 // =============================================================== Entity
 
-// An entity ID.
-#[derive(Clone, Hash, PartialEq, Eq, Debug)]
-pub struct Entity(pub u64);
+// An entity ID: the index of the slot of the entity, and the generation of the slot when it
+// was spawned. The slot of a deleted entity is reused with the next generation, so the IDs
+// of deleted entities never refer to the entities spawned after them.
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Entity {
+    pub index: u64,
+    pub generation: u32,
+}
 
 impl fmt::Display for Entity {
+    // The generation is only shown once the slot was reused.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Entity({})", self.0)
+        match self.generation {
+            0 => write!(f, "Entity({})", self.index),
+            generation => write!(f, "Entity({}v{})", self.index, generation),
+        }
+    }
+}
+
+// When and by which system an entity was deleted, for the errors of the IDs left behind.
+#[derive(Clone, Debug)]
+pub struct Deletion {
+    pub system: Option<&'static str>,
+    pub step: u64,
+}
+
+impl fmt::Display for Deletion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.system {
+            Some(system) => write!(f, "by {} at step {}", system, self.step),
+            None => write!(f, "at step {}", self.step),
+        }
+    }
+}
+
+// The ticks at which a component was added to its entity and last changed.
+#[derive(Clone, Copy, Debug)]
+struct Ticks {
+    added: u64,
+    changed: u64,
+}
+
+// Gets an entity from a variable.
+pub(crate) fn get_entity(var: &Var) -> Result<Entity> {
+    match var {
+        Var::Entity(entity) => Ok(entity.clone()),
+        var => Err(anyhow!("Expected an entity, but {} was provided.", var)),
     }
 }
 
@@ -64,14 +112,36 @@ impl fmt::Display for Entity {
 #[derive(Debug)]
 pub struct World {
     defs: &'static Map<Def>,
-    // Counter for entites ids.
-    counter: u64,
+    // Generation of each entity slot: the one of its entity, or of the next entity spawned in it.
+    generations: Vec<u32>,
+    // Indices of the slots without an entity, reused smallest first.
+    free: BTreeSet<u64>,
+    // When and by which system the last entity deleted in each slot was deleted, with its generation.
+    deletions: Vec<Option<(u32, Deletion)>>,
+    // The events sent during the current and the previous steps by type, with their sequence numbers.
+    events: Map<Vec<(u64, Var)>>,
+    // The sequence number of the next event sent.
+    next_event: u64,
+    // The sequence number of the first event sent during the current step.
+    step_events: u64,
+    // The sequence number of the next event each system reads, by system and type of event.
+    readers: HashMap<(&'static str, &'static str), u64>,
     // Map from resurces names to resources values.
     resources: Map<Var>,
     // Map from entities ID to their components.
     entities: HashMap<Entity, Map<Var>>,
-    // Map from EntityFilters to the entities that match them.
-    matches: HashMap<&'static EntityFilter, HashSet<Entity>>,
+    // The current tick, advanced each time a system starts.
+    tick: u64,
+    // The tick at which each system last started.
+    last_runs: HashMap<&'static str, u64>,
+    // The tick at which the running system started before, 0 if it never ran.
+    last_run: u64,
+    // Map from entities ID to the ticks of their components.
+    ticks: HashMap<Entity, Map<Ticks>>,
+    // The entity and name of each component, by the address of its struct, to track the writes to its fields.
+    owners: HashMap<*const RefCell<Struct>, (Entity, &'static str)>,
+    // Map from EntityFilters to the entities that match them, sorted by ID.
+    matches: HashMap<&'static EntityFilter, BTreeSet<Entity>>,
     // Map from entities ID to the filter they match.
     filters: HashMap<Entity, Vec<&'static EntityFilter>>,
 }
@@ -82,63 +152,228 @@ impl World {
     pub fn new(defs: &'static Map<Def>) -> World {
         World {
             defs,
-            counter: 0,
+            generations: Vec::new(),
+            free: BTreeSet::new(),
+            deletions: Vec::new(),
+            events: Map::default(),
+            next_event: 0,
+            step_events: 0,
+            readers: HashMap::default(),
             resources: Map::default(),
             entities: HashMap::default(),
+            tick: 0,
+            last_runs: HashMap::default(),
+            last_run: 0,
+            ticks: HashMap::default(),
+            owners: HashMap::default(),
             matches: HashMap::default(),
             filters: HashMap::default(),
         }
     }
 
+    // Replaces the definitions the world checks the types against.
+    pub fn set_defs(&mut self, defs: &'static Map<Def>) {
+        self.defs = defs;
+    }
+
+    // Returns all the resources, sorted by name.
+    pub fn resources(&self) -> Vec<(&'static str, Var)> {
+        let mut resources = self.resources.iter().map(|(name, var)| (*name, var.clone())).collect::<Vec<_>>();
+        resources.sort_by_key(|(name, _)| *name);
+        resources
+    }
+
     // Gets the resource.
-    pub fn get_resource(&self, name: &'static str) -> Result<Var> {
+    pub fn get_resource(&self, name: &str) -> Result<Var> {
         self.resources.get(name).cloned().ok_or_else(|| anyhow!("Resource {} not found", name))
     }
 
+    // Returns true if the named resource currently exists.
+    pub fn has_resource(&self, name: &str) -> Result<bool> {
+        if !self.is_resource(name) {
+            return Err(anyhow!("{} is not a resource.", name));
+        }
+        Ok(self.resources.contains_key(name))
+    }
+
+    // Returns true if the given name refers to an event.
+    pub fn is_event(&self, name: &str) -> bool {
+        matches!(self.defs.get(name), Some(Def::Event(_)))
+    }
+
+    // Returns true if the entity exists, that is it was spawned and not deleted.
+    pub fn is_alive(&self, entity: &Entity) -> bool {
+        self.entities.contains_key(entity)
+    }
+
+    // Returns the IDs of all the entities of the world, sorted.
+    pub fn entities(&self) -> Vec<Entity> {
+        let mut entities = self.entities.keys().cloned().collect::<Vec<_>>();
+        entities.sort();
+        entities
+    }
+
+    // Gets all the components of the given entity.
+    pub fn get_components(&self, entity: &Entity) -> Result<&Map<Var>> {
+        self.entities.get(entity).ok_or_else(|| self.missing(entity))
+    }
+
     // Gets the named component of the given entity.
     pub fn get_component(&self, entity: Entity, name: &str) -> Result<Var> {
-        self.entities.get(&entity)
-            .ok_or_else(|| anyhow!("Entity {} not found.", entity))?
-            .get(name).cloned()
+        self.component(&entity, name)?
+            .cloned()
             .ok_or_else(|| anyhow!("Component {} not found for {}.", name, entity))
     }
 
+    // Gets the named component of the given entity, or void if it doesn't have it.
+    pub fn get_optional_component(&self, entity: Entity, name: &str) -> Result<Var> {
+        Ok(self.component(&entity, name)?.cloned().unwrap_or(Var::Void))
+    }
+
+    // Returns true if the given entity has the named component.
+    pub fn has_component(&self, entity: Entity, name: &str) -> Result<bool> {
+        Ok(self.component(&entity, name)?.is_some())
+    }
+
+    // Reserves the ID of an entity to be spawned by a command, in a free slot or a new one.
+    pub fn reserve_entity(&mut self) -> Entity {
+        let index = self.free.pop_first().unwrap_or_else(|| {
+            self.generations.push(0);
+            self.generations.len() as u64 - 1
+        });
+        Entity {index, generation: self.generations[index as usize]}
+    }
+
+    // Advances the tick as the named system starts, remembering when it started before.
+    pub fn start_system(&mut self, name: &'static str) {
+        self.tick += 1;
+        self.last_run = self.last_runs.insert(name, self.tick).unwrap_or(0);
+    }
+
+    // Records a write to a field of the given struct, if it is a component.
+    pub fn set_changed(&mut self, s: &Shared<Struct>) {
+        if let Some((entity, name)) = self.owners.get(&Rc::as_ptr(s)) {
+            if let Some(ticks) = self.ticks.get_mut(entity).and_then(|ticks| ticks.get_mut(name)) {
+                ticks.changed = self.tick;
+            }
+        }
+    }
+
     // Executes the commands provided in the given vector of commands.
     pub fn do_commands(&mut self, commands: &mut Vec<Command>) -> Result<()> {
-        for cmd in commands.drain(..) {
-            match cmd {
-                Command::SpawnEntity(components) => self.spawn_entity(components)?,
-                Command::DeleteEntity(entity) => self.delete_entity(entity)?,
-                Command::NewResource(res) => self.new_resource(res)?,
+        let mut commands = commands.drain(..);
+        while let Some(cmd) = commands.next() {
+            if let Err(err) = self.do_command(cmd) {
+                self.discard_commands(commands);
+                return Err(err);
             }
         }
 
         Ok(())
     }
 
-    // Filter entites by components they should hold. Returns an iterator over the entities that matches the filter.
+    // Drops the given commands, the entities they would spawn give their slots back.
+    pub fn discard_commands(&mut self, commands: impl IntoIterator<Item = Command>) {
+        for cmd in commands {
+            if let Command::SpawnEntity(entity, _) = cmd {
+                self.release(&entity);
+            }
+        }
+    }
+
+    // Returns copies of the events of the given type sent since the given system last read them, as a list.
+    // Events are kept for two steps, so that systems running before the one sending them see them too.
+    pub fn read_events(&mut self, system: &'static str, name: &'static str) -> Var {
+        let next = self.readers.insert((system, name), self.next_event).unwrap_or(0);
+        let events = self.events.get(name).map(|events| {
+            events.iter().filter(|(id, _)| *id >= next).map(|(_, event)| event.deep_copy()).collect()
+        });
+        Var::List(as_shared(events.unwrap_or_default()))
+    }
+
+    // Drops the events sent before the previous step, as a new one starts.
+    pub fn clear_events(&mut self) {
+        let step_events = self.step_events;
+        for events in self.events.values_mut() {
+            events.retain(|(id, _)| *id >= step_events);
+        }
+        self.step_events = self.next_event;
+    }
+
+    // Writes a snapshot of the entities, components and resources to the file at the given path.
+    pub fn save(&self, path: &str) -> Result<()> {
+        Snapshot::save(path, &self.generations, &self.resources, &self.entities)
+    }
+
+    // Reads the snapshot at the given path, checking its values against the definitions.
+    pub fn load(&self, path: &str) -> Result<Snapshot> {
+        Snapshot::load(path, self.defs)
+    }
+
+    // Replaces the entities, components and resources with the ones of a snapshot.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.generations = snapshot.generations;
+        self.resources = snapshot.resources;
+        self.entities = snapshot.entities;
+        self.free = (0..self.generations.len() as u64)
+            .filter(|&index| !self.entities.contains_key(&Entity {index, generation: self.generations[index as usize]}))
+            .collect();
+        self.deletions.clear();
+
+        // The loaded components count as added, at a tick of their own so that even
+        // the systems running for the first time see them.
+        self.tick += 1;
+        self.ticks.clear();
+        self.owners.clear();
+        let entities = self.entities.iter()
+            .map(|(entity, components)| (entity.clone(), components.clone()))
+            .collect::<Vec<_>>();
+        for (entity, components) in entities {
+            self.stamp(&entity, &components, true);
+        }
+
+        // Clears the caches, the matches are computed again when the filters are next used.
+        self.matches.clear();
+        self.filters = self.entities.keys().map(|entity| (entity.clone(), Vec::new())).collect();
+    }
+
+    // Filter entites by components they should hold. Returns the entities that matches the filter,
+    // sorted by ID: systems and queries always visit entities in the order of their slots.
     pub fn filter_entities(&mut self, filter: &'static EntityFilter) -> Result<Vec<Entity>> {
+        // Only the entities whose components were added or changed since the running system last started.
+        let since = self.last_run;
+        let recent = |world: &World, entity: &Entity| {
+            let ticks = &world.ticks[entity];
+            filter.added.iter().all(|ty| ticks[ty].added > since)
+                && filter.changed.iter().all(|ty| ticks[ty].changed > since)
+        };
+
         // Successful cache match.
         if let Some(matches) = self.matches.get(filter) {
-            return Ok(matches.iter().cloned().collect());
+            return Ok(matches.iter().filter(|entity| recent(self, entity)).cloned().collect());
         }
 
         // Check if the filter contains only components
-        if filter.args.iter().any(|arg| !self.is_component(arg.ty)) {
-            return Err(anyhow!("Filter contains non-component types."));
+        let mut types = filter.args.iter().chain(filter.optional.iter()).map(|arg| arg.ty)
+            .chain(filter.without.iter().copied())
+            .chain(filter.added.iter().copied())
+            .chain(filter.changed.iter().copied());
+        if let Some(ty) = types.find(|ty| !self.is_component(ty)) {
+            return Err(anyhow!("Filter contains non-component type {}.", ty));
         }
 
         // It's a new filter, so we need to compute the entities it includes.
         let matches = self.entities.keys()
-            .filter_map(|entity| self.matches(filter, entity).then(|| entity.clone()))
-            .collect::<HashSet<_>>();
+            .filter(|entity| self.matches(filter, entity))
+            .cloned()
+            .collect::<BTreeSet<_>>();
     
         // Collects the matches.
-        let res = matches.iter().cloned().collect();
+        let res = matches.iter().filter(|entity| recent(self, entity)).cloned().collect();
 
         // Cache the matches.
         for entity in &matches {
-            self.filters.get_mut(&entity).unwrap().push(filter);
+            self.filters.get_mut(entity).unwrap().push(filter);
         }
         self.matches.insert(filter, matches);
 
@@ -158,15 +393,57 @@ impl World {
         matches!(self.defs.get(name), Some(Def::Resource(_)))
     }
 
+    // Gets the named component of the given entity if it has it. Fails if the entity
+    // doesn't exist or if the name isn't the one of a component.
+    fn component(&self, entity: &Entity, name: &str) -> Result<Option<&Var>> {
+        if !self.is_component(name) {
+            return Err(anyhow!("{} is not a component.", name));
+        }
+
+        Ok(self.entities.get(entity).ok_or_else(|| self.missing(entity))?.get(name))
+    }
+
+    // Returns the error for an entity that doesn't exist, telling when it was deleted if it was.
+    fn missing(&self, entity: &Entity) -> anyhow::Error {
+        let index = entity.index as usize;
+        match self.deletions.get(index).and_then(Option::as_ref) {
+            Some((generation, deletion)) if *generation == entity.generation => anyhow!("{} was deleted {}.", entity, deletion),
+            // An older entity of the slot, whose deletion was forgotten.
+            _ if self.generations.get(index).is_some_and(|generation| *generation != entity.generation) => {
+                anyhow!("{} was deleted.", entity)
+            }
+            _ => anyhow!("{} does not exist.", entity),
+        }
+    }
+
     // Returns true if the given entity matches the filter.
     fn matches(&self, filter: &EntityFilter, entity: &Entity) -> bool {
-        let components = self.entities.get(&entity).unwrap();
+        let components = self.entities.get(entity).unwrap();
         filter.args.iter().all(|arg| components.contains_key(arg.ty))
+            && filter.added.iter().chain(filter.changed.iter()).all(|ty| components.contains_key(ty))
+            && !filter.without.iter().any(|ty| components.contains_key(ty))
     }
 
-    // Spawn the entity with the given components.
-    fn spawn_entity(&mut self, components: Vec<Var>) -> Result<()> {
-        // Check if the components are valid.
+    // Recomputes the filters the given entity matches, updating both the matches
+    // cache and the filters cache.
+    fn update_matches(&mut self, entity: &Entity) {
+        for filter in self.filters.remove(entity).unwrap_or_default() {
+            self.matches.get_mut(filter).unwrap().remove(entity);
+        }
+
+        let matches = self.matches.keys()
+            .filter(|filter| self.matches(filter, entity))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for &filter in &matches {
+            self.matches.get_mut(filter).unwrap().insert(entity.clone());
+        }
+        self.filters.insert(entity.clone(), matches);
+    }
+
+    // Checks that the given variables are components, and maps them by name.
+    fn components_map(&self, components: Vec<Var>) -> Result<Map<Var>> {
         let mut map = Map::default();
 
         for component in components {
@@ -177,35 +454,144 @@ impl World {
             map.insert(name, component);
         }
 
-        // Add the entity to entities.
-        let entity = Entity(self.counter);
-        self.counter += 1;
+        Ok(map)
+    }
+
+    // Executes a single command.
+    fn do_command(&mut self, cmd: Command) -> Result<()> {
+        match cmd {
+            Command::SpawnEntity(entity, components) => self.spawn_entity(entity, components),
+            Command::DeleteEntity(entity, deletion) => self.delete_entity(entity, deletion),
+            Command::InsertComponents(entity, components) => self.insert_components(entity, components),
+            Command::RemoveComponent(entity, name) => self.remove_component(entity, &name),
+            Command::NewResource(res) => self.new_resource(res),
+            Command::SetResource(res) => self.set_resource(res),
+            Command::DeleteResource(name) => self.delete_resource(&name),
+            Command::SendEvent(event) => self.send_event(event),
+            Command::Load(snapshot) => {
+                self.restore(snapshot);
+                Ok(())
+            }
+        }
+    }
+
+    // Frees the slot reserved for an entity that won't be spawned, unless a world
+    // loaded since then already uses it.
+    fn release(&mut self, entity: &Entity) {
+        if self.generations.get(entity.index as usize) == Some(&entity.generation) && !self.entities.contains_key(entity) {
+            self.free.insert(entity.index);
+        }
+    }
+
+    // Sets the ticks of the given components of an entity to the current one, as they are
+    // added or replaced, and tracks the writes to their fields.
+    fn stamp(&mut self, entity: &Entity, components: &Map<Var>, added: bool) {
+        let ticks = self.ticks.entry(entity.clone()).or_default();
+        for (name, component) in components {
+            let added = match ticks.get(name) {
+                Some(ticks) if !added => ticks.added,
+                _ => self.tick,
+            };
+            ticks.insert(name, Ticks {added, changed: self.tick});
+            if let Var::Struct(s) = component {
+                self.owners.insert(Rc::as_ptr(s), (entity.clone(), name));
+            }
+        }
+    }
+
+    // Stops tracking the writes to the given components, as they are removed or replaced.
+    fn disown<'a>(&mut self, components: impl Iterator<Item = &'a Var>) {
+        for component in components {
+            if let Var::Struct(s) = component {
+                self.owners.remove(&Rc::as_ptr(s));
+            }
+        }
+    }
 
+    // Spawn the entity with the given reserved ID and components.
+    fn spawn_entity(&mut self, entity: Entity, components: Vec<Var>) -> Result<()> {
+        // Check if the components are valid, giving the slot back if they aren't.
+        let map = match self.components_map(components) {
+            Ok(map) => map,
+            Err(err) => {
+                self.release(&entity);
+                return Err(err);
+            }
+        };
+
+        // A world loaded after the ID was reserved may not have its slot free anymore.
+        if self.generations.get(entity.index as usize) != Some(&entity.generation) || self.entities.contains_key(&entity) {
+            return Err(anyhow!("{} cannot be spawned, a world was loaded after its ID was given.", entity));
+        }
+        self.free.remove(&entity.index);
+
+        // Add the entity to entities.
+        self.stamp(&entity, &map, true);
         self.entities.insert(entity.clone(), map);
 
         // Update matches cache as well as filters cache.
-        let matches = self.matches.keys()
-            .filter(|filter| self.matches(filter, &entity))
-            .map(|&filter| filter)
-            .collect::<Vec<_>>();
+        self.update_matches(&entity);
 
-        for &filter in &matches {
-            self.matches.get_mut(filter).unwrap().insert(entity.clone());
-        }
-        self.filters.insert(entity, matches);
+        Ok(())
+    }
+
+    // Adds the given components to an existing entity, replacing the ones it already has.
+    fn insert_components(&mut self, var: Var, components: Vec<Var>) -> Result<()> {
+        let entity = get_entity(&var)?;
+        let map = self.components_map(components)?;
+
+        // Replaced components count as changed, the others as added.
+        let replaced = match self.entities.get_mut(&entity) {
+            Some(components) => map.iter()
+                .filter_map(|(name, component)| components.insert(name, component.clone()))
+                .collect::<Vec<_>>(),
+            None => return Err(self.missing(&entity)),
+        };
+        self.disown(replaced.iter());
+        self.stamp(&entity, &map, false);
+
+        self.update_matches(&entity);
 
         Ok(())
     }
 
-    // Delete the entity with the given ID.
-    fn delete_entity(&mut self, var: Var) -> Result<()> {
-        let entity = match var {
-            Var::Entity(e) => e,
-            _ => return Err(anyhow!("Expected an entity to Delete, got: {}.", var))
+    // Removes the named component from an existing entity.
+    fn remove_component(&mut self, var: Var, name: &str) -> Result<()> {
+        let entity = get_entity(&var)?;
+
+        if !self.is_component(name) {
+            return Err(anyhow!("{} is not a component.", name));
+        }
+
+        let components = match self.entities.get_mut(&entity) {
+            Some(components) => components,
+            None => return Err(self.missing(&entity)),
         };
+        let component = components.remove(name).ok_or_else(|| anyhow!("Component {} not found for {}.", name, entity))?;
+        self.disown(std::iter::once(&component));
+        self.ticks.get_mut(&entity).unwrap().remove(name);
+
+        self.update_matches(&entity);
+
+        Ok(())
+    }
+
+    // Delete the entity with the given ID, freeing its slot for the next generation.
+    fn delete_entity(&mut self, var: Var, deletion: Deletion) -> Result<()> {
+        let entity = get_entity(&var)?;
 
         // Remove the entity from entities.
-        self.entities.remove(&entity).ok_or_else(|| anyhow!("Entity {} not found.", entity))?;
+        let components = self.entities.remove(&entity).ok_or_else(|| self.missing(&entity))?;
+        self.disown(components.values());
+        self.ticks.remove(&entity);
+        // After 2^32 deletions in a slot, its generations start over.
+        let index = entity.index as usize;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.insert(entity.index);
+        if self.deletions.len() <= index {
+            self.deletions.resize(index + 1, None);
+        }
+        self.deletions[index] = Some((entity.generation, deletion));
 
         // Update matches cache and remove the corresponding entry in filters.
         let matches = self.filters.remove(&entity).unwrap();
@@ -231,4 +617,39 @@ impl World {
     
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Creates or replaces the resource with the given variable.
+    fn set_resource(&mut self, res: Var) -> Result<()> {
+        let name = res.struct_type()?;
+
+        if !self.is_resource(name) {
+            return Err(anyhow!("{} is not a resource.", name));
+        }
+
+        self.resources.insert(name, res);
+        Ok(())
+    }
+
+    // Deletes the resource with the given name.
+    fn delete_resource(&mut self, name: &str) -> Result<()> {
+        if !self.is_resource(name) {
+            return Err(anyhow!("{} is not a resource.", name));
+        }
+
+        self.resources.remove(name).ok_or_else(|| anyhow!("Resource {} not found.", name))?;
+        Ok(())
+    }
+
+    // Adds an event to the ones of its type.
+    fn send_event(&mut self, event: Var) -> Result<()> {
+        let name = event.struct_type()?;
+
+        if !self.is_event(name) {
+            return Err(anyhow!("{} is not an event.", name));
+        }
+
+        self.events.entry(name).or_default().push((self.next_event, event));
+        self.next_event += 1;
+        Ok(())
+    }
+}
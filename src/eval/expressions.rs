@@ -1,18 +1,27 @@
+use std::rc::Rc;
+
 use super::*;
 
 // Evaluates an expression.
 pub fn eval_expr(ctx: &Context, scope: &Scope, expr: &'static ast::Expr) -> Result<Var> {
-    match expr {
-        ast::Expr::Ternary(ternary) => eval_ternary(ctx, scope, ternary),
-        ast::Expr::Assign(assign) => eval_assign(ctx, scope, assign),
-        ast::Expr::Atom(atom) => eval_atom(atom),
-        ast::Expr::LValue(lvalue) => eval_lvalue(ctx, scope, lvalue),
-        ast::Expr::ListInit(list_init) => eval_list_init(ctx, scope, list_init),
-        ast::Expr::StructInit(struct_init) => eval_struct_init(ctx, scope, struct_init),
-        ast::Expr::Call(call) => eval_call(ctx, scope, call),
-        ast::Expr::BinExpr(bin_expr) => eval_bin_expr(ctx, scope, bin_expr),
-        ast::Expr::UnExpr(un_expr) => eval_un_expr(ctx, scope, un_expr),
-    }
+    match &expr.kind {
+        ast::ExprKind::Ternary(ternary) => eval_ternary(ctx, scope, ternary),
+        ast::ExprKind::Assign(assign) => eval_assign(ctx, scope, assign),
+        ast::ExprKind::Atom(atom) => eval_atom(atom),
+        ast::ExprKind::LValue(lvalue) => eval_lvalue(ctx, scope, lvalue),
+        ast::ExprKind::ListInit(list_init) => eval_list_init(ctx, scope, list_init),
+        ast::ExprKind::StructInit(struct_init) => eval_struct_init(ctx, scope, struct_init),
+        ast::ExprKind::EnumInit(enum_init) => eval_enum_init(ctx, scope, enum_init),
+        ast::ExprKind::Incr(incr) => eval_incr(ctx, scope, incr),
+        ast::ExprKind::Call(call) => eval_call(ctx, scope, call),
+        ast::ExprKind::Function(def) => Ok(Var::Function(Rc::new(Closure {
+            name: None,
+            def,
+            captures: scope.captures().into_iter().filter(|(name, _)| !def.args.contains(name)).collect(),
+        }))),
+        ast::ExprKind::BinExpr(bin_expr) => eval_bin_expr(ctx, scope, bin_expr),
+        ast::ExprKind::UnExpr(un_expr) => eval_un_expr(ctx, scope, un_expr),
+    }.at(expr.span)
 }
 
 // Evaluates a ternary expression.
@@ -20,7 +29,7 @@ pub fn eval_ternary(ctx: &Context, scope: &Scope, ternary: &'static ast::Ternary
     match eval_expr(ctx, scope, &ternary.cond)? {
         Var::Bool(true) => eval_expr(ctx, scope, &ternary.branch1),
         Var::Bool(false) => eval_expr(ctx, scope, &ternary.branch2),
-        _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in an if statement.")),
+        _ => Err(anyhow!("A condition expression evaluated to a non-boolean value in an if statement.")),
     }
 }
 
@@ -44,15 +53,17 @@ pub fn eval_list_init(ctx: &Context, scope: &Scope, list_init: &'static ast::Lis
 // Evaluates a struct initialization.
 pub fn eval_struct_init(ctx: &Context, scope: &Scope, struct_init: &'static ast::StructInit) -> Result<Var> {
     match ctx.get_def(struct_init.name)? {
-        Def::Component(def) | Def::Resource(def) | Def::Struct(def) => {
+        Def::Component(def) | Def::Resource(def) | Def::Event(def) | Def::Struct(def) => {
             let mut map = Map::with_capacity(def.fields.len());
 
             for (name, expr) in struct_init.fields.iter() {
-                if !def.fields.contains_key(name) {
-                    return Err(anyhow!("{} is not a field of {}.", name, struct_init.name));
-                }
+                let ty = def.fields.get(name)
+                    .ok_or_else(|| anyhow!("{} is not a field of {}.", name, struct_init.name))?;
 
-                if map.insert(name, eval_expr(ctx, scope, expr)?).is_some() {
+                let var = eval_expr(ctx, scope, expr)?;
+                check_field(struct_init.name, name, ty, &var).at(expr.span)?;
+
+                if map.insert(name, var).is_some() {
                     return Err(anyhow!("{} is already initialized.", name));
                 }
             }
@@ -66,6 +77,45 @@ pub fn eval_struct_init(ctx: &Context, scope: &Scope, struct_init: &'static ast:
                 map
             })))
         },
-        _ => return Err(anyhow!("{} is not a struct type.", struct_init.name)),
+        _ => Err(anyhow!("{} is not a struct type.", struct_init.name)),
+    }
+}
+// Evaluates an enum value.
+pub fn eval_enum_init(ctx: &Context, scope: &Scope, enum_init: &'static ast::EnumInit) -> Result<Var> {
+    let (name, def) = match ctx.get_def_entry(enum_init.name)? {
+        (name, Def::Enum(def)) => (name, def),
+        _ => return Err(anyhow!("{} is not an enum.", enum_init.name)),
+    };
+
+    let variant = def.variant(enum_init.variant)
+        .ok_or_else(|| anyhow!("{} is not a variant of {}.", enum_init.variant, name))?;
+
+    if variant.fields.len() != enum_init.args.len() {
+        return Err(anyhow!("{}::{} holds {} values, but {} were given.", name, variant.name, variant.fields.len(), enum_init.args.len()));
+    }
+
+    let mut args = Vec::with_capacity(enum_init.args.len());
+    for (ty, expr) in variant.fields.iter().zip(enum_init.args.iter()) {
+        let var = eval_expr(ctx, scope, expr)?;
+        check_payload(name, variant.name, ty, &var).at(expr.span)?;
+        args.push(var);
+    }
+
+    Ok(Var::Enum(Rc::new(Enum {name, variant: variant.name, args})))
+}
+
+// Checks the type of the value given to a field of a struct.
+pub fn check_field(struct_name: &str, name: &str, ty: &ast::Type, var: &Var) -> Result<()> {
+    match var.get_type() == *ty {
+        true => Ok(()),
+        false => Err(anyhow!("Field {} of {} should be of type {}, but {} was given.", name, struct_name, ty, var)),
     }
-}
\ No newline at end of file
+}
+
+// Checks the type of a value held by a variant of an enum.
+pub fn check_payload(name: &str, variant: &str, ty: &ast::Type, var: &Var) -> Result<()> {
+    match var.get_type() == *ty {
+        true => Ok(()),
+        false => Err(anyhow!("{}::{} should hold a value of type {}, but {} was given.", name, variant, ty, var)),
+    }
+}
@@ -1,16 +1,26 @@
 use super::*;
 
-// Gets an int value from an expression.
-fn get_usize(ctx: &Context, scope: &Scope, expr: &'static ast::Expr) -> Result<usize> {
-    match eval_expr(ctx, scope, expr)? {
+// Converts a value to an index.
+pub fn as_index(var: Var) -> Result<usize> {
+    match var {
         Var::Int(i) => Ok(i as usize),
         _ => Err(anyhow!("Expected an integer index.")),
     }
 }
 
+// Gets an int value from an expression.
+fn get_usize(ctx: &Context, scope: &Scope, expr: &'static ast::Expr) -> Result<usize> {
+    as_index(eval_expr(ctx, scope, expr)?)
+}
+
 // Gets a value from a list.
 fn get_list(ctx: &Context, scope: &Scope, list: Var, index: &'static ast::Expr) -> Result<Var> {
     let i = get_usize(ctx, scope, index)?;
+    get_item(list, i)
+}
+
+// Gets the item at the given index of a list or a string.
+pub fn get_item(list: Var, i: usize) -> Result<Var> {
     match list {
         Var::String(s) => Ok(Var::Char(s.chars().nth(i).ok_or_else(|| anyhow!("Index out of bounds."))?)),
         Var::List(list) => Ok(list.borrow().get(i).ok_or_else(|| anyhow!("Index out of bounds."))?.clone()),
@@ -27,96 +37,180 @@ fn get_index(ctx: &Context, scope: &Scope, mut var: Var, index: &'static ast::In
 }
 
 // Gets a value from a struct.
-fn get_struct(s: Var, name: &'static str) -> Result<Var> {
+pub fn get_struct(s: Var, name: &'static str) -> Result<Var> {
     match s {
         Var::Struct(s) => Ok(s.borrow().map.get(name).ok_or_else(|| anyhow!("{} is not a field of {}.", name, s.borrow().name))?.clone()),
         _ => Err(anyhow!("Expected a struct.")),
     }
 }
 
-// Sets a value in a struct.
-fn set_struct(s: Var, name: &'static str, val: Var) -> Result<()> {
-    match s {
-        Var::Struct(s) => {      
-            let mut borrow = s.borrow_mut();
-            
-            match borrow.map.get_mut(name) {
-                Some(var) if var.get_type() == val.get_type() => *var = val, 
-                _ => return Err(anyhow!("{} is not a field of {}.", name, borrow.name)),
-            }
-            
-            Ok(())
+// Evaluates a left value.
+pub fn eval_lvalue(ctx: &Context, scope: &Scope, lvalue: &'static ast::LValue) -> Result<Var> {
+    let mut var = match scope.get_var(lvalue.name) {
+        Ok(var) => var,
+        // The name of a struct-like type, that isn't shadowed by a variable, evaluates to a string,
+        // and the name of a function to the function.
+        Err(err) if lvalue.first_index.exprs.is_empty() && lvalue.path.is_empty() => match ctx.get_def_entry(lvalue.name) {
+            Ok((_, Def::Component(_) | Def::Resource(_) | Def::Event(_) | Def::Struct(_))) => return Ok(Var::String(lvalue.name.to_string())),
+            Ok((name, Def::Function(def))) => return Ok(function_value(name, def)),
+            _ => return Err(err).at(lvalue.span),
         },
-        _ => Err(anyhow!("Expected a struct.")),
+        Err(err) => return Err(err).at(lvalue.span),
+    };
+
+    var = get_index(ctx, scope, var, &lvalue.first_index).at(lvalue.span)?;
+
+    for (name, index) in &lvalue.path {
+        var = get_struct(var, name).at(lvalue.span)?;
+        var = get_index(ctx, scope, var, index).at(lvalue.span)?;
     }
+
+    Ok(var)
 }
 
-fn set_list(ctx: &Context, scope: &Scope, mut var: Var, index: &'static ast::Index, val: Var) -> Result<()> {
-    for expr in &index.exprs[..index.exprs.len()-1] {
-        var = get_list(ctx, scope, var, expr)?;
+// A location a value can be assigned to. Indices and fields keep the variable their path starts from,
+// so that assigning within a component marks it as changed.
+enum Place {
+    Var(&'static str),
+    Index(Shared<Vec<Var>>, usize, Var),
+    Field(Shared<Struct>, &'static str, Var),
+}
+
+impl Place {
+    // Gets the value at that location.
+    fn get(&self, scope: &Scope) -> Result<Var> {
+        match self {
+            Place::Var(name) => scope.get_var(name),
+            Place::Index(list, i, _) => get_at(list, *i),
+            Place::Field(s, name, _) => get_struct(Var::Struct(s.clone()), name),
+        }
     }
-    let i = get_usize(ctx, scope, &index.exprs.last().unwrap())?;
 
-    match var {
-        Var::List(list) => {      
-            let mut borrow = list.borrow_mut();
-            
-            match borrow.get_mut(i) {
-                Some(var) => *var = val, 
-                _ => return Err(anyhow!("Index {} out of bounds.", i)),
+    // Sets the value at that location. Fields keep their type.
+    fn set(&self, ctx: &Context, scope: &Scope, val: Var) -> Result<()> {
+        match self {
+            Place::Var(name) => scope.set_var(name, val),
+            Place::Index(list, i, root) => {
+                set_at(list, *i, val)?;
+                set_root_changed(ctx, root);
+                Ok(())
+            }
+            Place::Field(s, name, root) => {
+                set_field(s, name, val)?;
+                ctx.world_mut().set_changed(s);
+                set_root_changed(ctx, root);
+                Ok(())
             }
-            
-            Ok(())
         }
-        _ => Err(anyhow!("Expected a struct.")),
     }
 }
 
-// Evaluates a left value.
-pub fn eval_lvalue(ctx: &Context, scope: &Scope, lvalue: &'static ast::LValue) -> Result<Var> {
-    let mut var = scope.get_var(&lvalue.name)?;
+// Marks the struct a path of indices and fields starts from as changed, if it is a component.
+pub fn set_root_changed(ctx: &Context, root: &Var) {
+    if let Var::Struct(s) = root {
+        ctx.world_mut().set_changed(s);
+    }
+}
 
-    var = get_index(ctx, scope, var, &lvalue.first_index)?;
+// Gets the item at the given index of a list, to be assigned.
+pub fn get_at(list: &Shared<Vec<Var>>, i: usize) -> Result<Var> {
+    list.borrow().get(i).cloned().ok_or_else(|| anyhow!("Index {} out of bounds.", i))
+}
 
-    for (name, index) in &lvalue.path {
-        var = get_struct(var, name)?;
-        var = get_index(ctx, scope, var, index)?;
+// Sets the item at the given index of a list.
+pub fn set_at(list: &Shared<Vec<Var>>, i: usize, val: Var) -> Result<()> {
+    match list.borrow_mut().get_mut(i) {
+        Some(var) => *var = val,
+        None => return Err(anyhow!("Index {} out of bounds.", i)),
     }
+    Ok(())
+}
 
-    Ok(var)
+// Sets a field of a struct, that keeps its type.
+pub fn set_field(s: &Shared<Struct>, name: &'static str, val: Var) -> Result<()> {
+    let mut borrow = s.borrow_mut();
+    let struct_name = borrow.name;
+    match borrow.map.get_mut(name) {
+        Some(var) if var.get_type() == val.get_type() => *var = val,
+        Some(var) => return Err(anyhow!(
+            "Field {} of {} should be of type {}, but {} was given.", name, struct_name, var.get_type(), val
+        )),
+        None => return Err(anyhow!("{} is not a field of {}.", name, struct_name)),
+    }
+    Ok(())
+}
+
+// Checks that a variable holding the given value can be reassigned.
+pub fn check_reassign(var: &Var) -> Result<()> {
+    match var {
+        Var::Struct(_) | Var::List(_) => Err(anyhow!("Cannot reassign to a struct or list variable in a ctx.")),
+        _ => Ok(()),
+    }
 }
 
-// Evaluates an assignment expression.
+// Finds the location a left value refers to, evaluating its indices once.
+fn get_place(ctx: &Context, scope: &Scope, lvalue: &'static ast::LValue) -> Result<Place> {
+    // The indices and fields to follow, in order.
+    let mut steps = lvalue.first_index.exprs.iter().map(Either::Left)
+        .chain(lvalue.path.iter().flat_map(|(name, index)| {
+            std::iter::once(Either::Right(*name)).chain(index.exprs.iter().map(Either::Left))
+        }));
+
+    let mut last = match steps.next() {
+        Some(step) => step,
+        None => return Ok(Place::Var(lvalue.name)),
+    };
+
+    let root = scope.get_var(lvalue.name)?;
+    let mut var = root.clone();
+    for step in steps {
+        var = match last {
+            Either::Left(expr) => get_list(ctx, scope, var, expr)?,
+            Either::Right(name) => get_struct(var, name)?,
+        };
+        last = step;
+    }
+
+    match (last, var) {
+        (Either::Left(expr), Var::List(list)) => Ok(Place::Index(list, get_usize(ctx, scope, expr)?, root)),
+        (Either::Left(_), _) => Err(anyhow!("Expected a list.")),
+        (Either::Right(name), Var::Struct(s)) => Ok(Place::Field(s, name, root)),
+        (Either::Right(_), _) => Err(anyhow!("Expected a struct.")),
+    }
+}
+
+// Evaluates an assignment expression, that may be compound like +=.
 pub fn eval_assign(ctx: &Context, scope: &Scope, assign: &'static ast::Assign) -> Result<Var> {
     let val = eval_expr(ctx, scope, &assign.expr)?;
-    let ret = val.clone();
-    let lvalue = &assign.lvalue;
-
-    if lvalue.path.is_empty() {
-        if lvalue.first_index.exprs.is_empty() {
-            scope.set_var(&lvalue.name, val)?;
-        } else {
-            let var = scope.get_var(&lvalue.name)?;
-            set_list(ctx, scope, var, &lvalue.first_index, val)?;
-        }
-    } else {
-        let mut var = scope.get_var(&lvalue.name)?;
-        var = get_index(ctx, scope, var, &lvalue.first_index)?;
 
-        for (name, index) in &lvalue.path[..lvalue.path.len()-1] {
-            var = get_struct(var, name)?;
-            var = get_index(ctx, scope, var, index)?;
-        }
+    let place = get_place(ctx, scope, &assign.lvalue).at(assign.lvalue.span)?;
+    let val = match assign.op {
+        Some(op) => apply_bin_op(place.get(scope).at(assign.lvalue.span)?, op, val)?,
+        None => val,
+    };
 
-        let (name, index) = lvalue.path.last().unwrap();
+    place.set(ctx, scope, val.clone()).at(assign.lvalue.span)?;
+    Ok(val)
+}
 
-        if index.exprs.is_empty() {
-            set_struct(var, name, val)?;
-        } else {
-            var = get_struct(var, name)?;
-            set_list(ctx, scope, var, index, val)?;
-        }
+// Evaluates an increment or a decrement.
+pub fn eval_incr(ctx: &Context, scope: &Scope, incr: &'static ast::Incr) -> Result<Var> {
+    let place = get_place(ctx, scope, &incr.lvalue).at(incr.lvalue.span)?;
+    let old = place.get(scope).at(incr.lvalue.span)?;
+    let new = incremented(&old, incr.op)?;
+
+    place.set(ctx, scope, new.clone()).at(incr.lvalue.span)?;
+    Ok(if incr.prefix {new} else {old})
+}
+
+// Computes the incremented or decremented value of a number.
+pub fn incremented(old: &Var, op: ast::IncrOp) -> Result<Var> {
+    let op = match op {
+        ast::IncrOp::Inc => ast::BinOp::Add,
+        ast::IncrOp::Dec => ast::BinOp::Sub,
+    };
+    match old {
+        Var::Int(_) | Var::Float(_) => apply_bin_op(old.clone(), op, Var::Int(1)),
+        _ => Err(anyhow!("Cannot increment or decrement {}.", old)),
     }
-    
-    Ok(ret)
-}
\ No newline at end of file
+}
@@ -1,25 +1,39 @@
 use anyhow::{anyhow, Result};
 
 use crate::ast;
+use crate::sources::WithSpan;
 use crate::utils::*;
 
+mod builtins;
+use builtins::*;
+
 mod calls;
 use calls::*;
+pub use calls::{as_function, check_arg, check_args, check_return, function_value};
 
 mod context;
-use context::*;
+pub use context::*;
 
 mod ecs;
-use ecs::*;
+pub use ecs::*;
 
 mod expressions;
 use expressions::*;
+pub use expressions::{check_field, check_payload, eval_atom};
 
 mod lvalues;
 use lvalues::*;
+pub use lvalues::{as_index, check_reassign, get_at, get_item, get_struct, incremented, set_at, set_field, set_root_changed};
+
+mod natives;
+pub use natives::*;
 
 mod operator;
 use operator::*;
+pub use operator::{apply_bin_op, apply_un_op};
+
+mod snapshot;
+pub use snapshot::*;
 
 mod statements;
 use statements::*;
@@ -28,49 +42,62 @@ mod systems;
 use systems::*;
 
 mod vars;
-use vars::*;
+pub use vars::*;
 
-// Walks the AST, interpreting the code.
-pub fn eval(ast: &'static ast::AST) -> Result<()> {
+// Collects the definitions of all the names of the AST.
+pub fn definitions(ast: &'static ast::AST) -> Result<&'static Map<Def>> {
     let mut defs = Box::new(Map::default());
 
-    // Gets all definitions.
     for (name, element) in ast.names.iter() {
-        let def = match element {
-            ast::Name::Function(fun) => Def::Function(fun),
-            ast::Name::System(sys) => Def::System(sys),
-            ast::Name::Component(comp) => Def::Component(comp),
-            ast::Name::Resource(res) => Def::Resource(res),
-            ast::Name::Struct(struct_) => Def::Struct(struct_),
-        };
-
-        if defs.insert(name, def).is_some() {
+        if defs.insert(name, definition(element)).is_some() {
             return Err(anyhow!("object with name '{}' already exists", name));
         }
     }
 
-    let mut ctx = Context::new(Box::leak(defs));
-
-    // Runs a system by it's name.
-    let run_system = |name| match ctx.get_def(name)? {
-        Def::System(sys) => eval_system(&ctx, sys),
-        _ => Err(anyhow!("{} is not a system", name)),
-    };
-
-    // Runs all "Init" systems.
-    for name in ast.init.iter() {
-        run_system(name)?;
+    Ok(Box::leak(defs))
+}
+
+// Returns the definition of a name of the AST.
+pub fn definition(element: &'static ast::Name) -> Def {
+    match element {
+        ast::Name::Function(fun) => Def::Function(fun),
+        ast::Name::System(sys) => Def::System(sys),
+        ast::Name::Component(comp) => Def::Component(comp),
+        ast::Name::Resource(res) => Def::Resource(res),
+        ast::Name::Event(event) => Def::Event(event),
+        ast::Name::Struct(struct_) => Def::Struct(struct_),
+        ast::Name::Enum(enum_) => Def::Enum(enum_),
     }
-
-    // Exits if there are no systems to run in a loop.
-    if ast.run.is_empty() {
-        return Ok(());
+}
+
+// Evaluates statements in the given scope, outside of any function or system, applying
+// their commands. Returns the value of the last statement if it is an expression.
+pub fn eval_input(ctx: &Context, scope: &Scope, block: &'static ast::Block) -> Result<Option<Var>> {
+    let mut last = None;
+
+    for stmt in &block.statements {
+        last = match &stmt.kind {
+            ast::StatementKind::Expr(expr) => Some(eval_expr(ctx, scope, expr)?),
+            _ => {
+                match eval_statement(ctx, scope, stmt)? {
+                    Flow::Ok => (),
+                    Flow::Break => return Err(anyhow!("Cannot break outside of a loop.")).at(stmt.span),
+                    Flow::Continue => return Err(anyhow!("Cannot continue outside of a loop.")).at(stmt.span),
+                    Flow::Return(_) => return Err(anyhow!("Cannot return outside of a function.")).at(stmt.span),
+                }
+                None
+            }
+        };
     }
 
-    // Runs all "Run" systems in a loop.
-    loop {
-        for name in ast.run.iter() {
-            run_system(name)?;
-        }
+    ctx.update()?;
+    Ok(last)
+}
+
+// Runs a system by it's name.
+pub fn run_system(ctx: &Context, name: &str) -> Result<()> {
+    match ctx.get_def_entry(name)? {
+        (name, Def::System(sys)) => ctx.in_system(name, || eval_system(ctx, sys)),
+        _ => Err(anyhow!("{} is not a system", name)),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::*;
+
+// The Rust closure implementing a native function.
+pub type NativeFn = Rc<dyn Fn(&Context, Vec<Var>) -> Result<Var>>;
+
+// A function implemented in Rust, that scripts can call.
+#[derive(Clone)]
+pub struct Native {
+    // The types of the parameters, None meaning any type is accepted.
+    // If there is no list of parameters, the function is variadic.
+    pub params: Option<Vec<Option<ast::Type>>>,
+    pub fun: NativeFn,
+}
+
+impl Native {
+    // Creates a native function with the given parameters.
+    pub fn new(params: &[Option<ast::Type>], fun: impl Fn(&Context, Vec<Var>) -> Result<Var> + 'static) -> Native {
+        Native {
+            params: Some(params.to_vec()),
+            fun: Rc::new(fun),
+        }
+    }
+
+    // Creates a native function that accepts any number of arguments, of any type.
+    pub fn variadic(fun: impl Fn(&Context, Vec<Var>) -> Result<Var> + 'static) -> Native {
+        Native {
+            params: None,
+            fun: Rc::new(fun),
+        }
+    }
+
+    // Checks the arguments against the parameters and calls the function.
+    pub fn call(&self, ctx: &Context, name: &str, args: Vec<Var>) -> Result<Var> {
+        if let Some(params) = &self.params {
+            if params.len() != args.len() {
+                return Err(anyhow!("{} expected exactly {} arguments, but {} where provided", name, params.len(), args.len()));
+            }
+
+            for (i, (param, arg)) in params.iter().zip(args.iter()).enumerate() {
+                match param {
+                    Some(ty) if *ty != arg.get_type() => return Err(anyhow!(
+                        "Argument {} of {} should be of type {}, but {} was provided.", i + 1, name, ty, arg
+                    )),
+                    _ => (),
+                }
+            }
+        }
+
+        (self.fun)(ctx, args)
+    }
+}
+
+impl fmt::Debug for Native {
+    // Closures can't be printed, only the parameters are.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Native").field("params", &self.params).finish_non_exhaustive()
+    }
+}
+
+// The native functions, by name.
+pub type Natives = HashMap<String, Native>;
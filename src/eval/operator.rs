@@ -8,8 +8,12 @@ use super::*;
 pub fn eval_bin_expr(ctx: &Context, scope: &Scope, bin_expr: &'static ast::BinExpr) -> Result<Var> {
     let lvar = eval_expr(ctx, scope, &bin_expr.left)?;
     let rvar = eval_expr(ctx, scope, &bin_expr.right)?;
-    
-    Ok(match (lvar.clone(), bin_expr.op, rvar.clone()) {
+    apply_bin_op(lvar, bin_expr.op, rvar)
+}
+
+// Applies a binary operator to two values.
+pub fn apply_bin_op(lvar: Var, op: ast::BinOp, rvar: Var) -> Result<Var> {
+    Ok(match (lvar.clone(), op, rvar.clone()) {
         (Int(i), Add, Int(j)) => Int(i + j),
         (Float(x), Add, Float(y)) => Float(x + y),
         (Char(c), Add, Char(d)) => String(format!("{}{}", c, d)),
@@ -27,6 +31,7 @@ pub fn eval_bin_expr(ctx: &Context, scope: &Scope, bin_expr: &'static ast::BinEx
         (Int(i), Mul, Float(x)) | (Float(x), Mul, Int(i)) => Float(i as f64 * x),
         (String(s), Mul, Int(i)) | (Int(i), Mul, String(s)) => String(s.repeat(i as usize)),
 
+        (Int(_), Div | Mod, Int(0)) => return Err(anyhow!("Division by zero.")),
         (Int(i), Div, Int(j)) => Int(i / j),
         (Float(x), Div, Float(y)) => Float(x / y),
         (Int(i), Div, Float(x)) => Float(i as f64 / x),
@@ -79,20 +84,24 @@ pub fn eval_bin_expr(ctx: &Context, scope: &Scope, bin_expr: &'static ast::BinEx
         (Int(i), Eq, Float(x)) | (Float(x), Eq, Int(i)) => Bool(i as f64 != x),
         (var1, Neq, var2) => Bool(!var1.eq(&var2)),
         
-        _ => return Err(anyhow!("Binary operator {:?} is not defined for {} and {}", bin_expr.op, lvar, rvar)),
+        _ => return Err(anyhow!("Binary operator {:?} is not defined for {} and {}", op, lvar, rvar)),
     })
 }
 
 // Evaluates an unary expression.
 pub fn eval_un_expr(ctx: &Context, scope: &Scope, un_expr: &'static ast::UnExpr) -> Result<Var> {
     let var = eval_expr(ctx, scope, &un_expr.expr)?;
-    
-    Ok(match (un_expr.op, var.clone()) {
+    apply_un_op(un_expr.op, var)
+}
+
+// Applies an unary operator to a value.
+pub fn apply_un_op(op: ast::UnOp, var: Var) -> Result<Var> {
+    Ok(match (op, var.clone()) {
         (Pos, Int(_)) | (Pos, Float(_)) => var,
         (Neg, Int(i)) => Int(-i),
         (Neg, Float(x)) => Float(-x),
         (Not, Bool(b)) => Bool(!b),
         (BitNot, Int(i)) => Int(!i),
-        _ => return Err(anyhow!("Unary operator {:?} is not defined for {}", un_expr.op, var)),
+        _ => return Err(anyhow!("Unary operator {:?} is not defined for {}", op, var)),
     })
 }
\ No newline at end of file
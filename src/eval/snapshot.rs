@@ -0,0 +1,279 @@
+// Snapshots of the world, saved as JSON.
+//
+// A snapshot is an object holding the version of the format, the counter giving the
+// number of entity slots, the generation of each slot, the resources by name and the
+// entities with their components by name. Resources and components are objects holding
+// their fields:
+//
+//     {
+//       "version": 2,
+//       "counter": 2,
+//       "generations": [1, 0],
+//       "resources": {"Score": {"value": 10}},
+//       "entities": [
+//         {"id": 1, "components": {"Name": {"value": "Alice"}, "Position": {"x": 1.5, "y": 0.0}}}
+//       ]
+//     }
+//
+// The ID of an entity is the index of its slot, and its generation the one of the slot.
+// Snapshots of the first version have no generations, they are all 0.
+//
+// Void, booleans, ints, floats, strings and lists are the matching JSON values, floats
+// always having a decimal point. The other values are tagged objects:
+// - a char is {"char": "c"},
+// - an entity is {"entity": 1, "generation": 0},
+// - a struct is {"struct": "Point", "fields": {"x": 1, "y": 2}},
+// - an enum is {"enum": "Shape", "variant": "Circle", "args": [1.0]}.
+
+use std::collections::HashMap;
+use std::fs;
+use std::rc::Rc;
+
+use serde_json::{json, Value as Json};
+
+use super::*;
+
+// The version of the format, to change when it does.
+const VERSION: u64 = 2;
+
+// The content of a world, read from a snapshot.
+#[derive(Debug)]
+pub struct Snapshot {
+    pub generations: Vec<u32>,
+    pub resources: Map<Var>,
+    pub entities: HashMap<Entity, Map<Var>>,
+}
+
+impl Snapshot {
+    // Writes a snapshot of the given content of a world to the file at the given path.
+    pub fn save(path: &str, generations: &[u32], resources: &Map<Var>, entities: &HashMap<Entity, Map<Var>>) -> Result<()> {
+        let mut entities = entities.iter().collect::<Vec<_>>();
+        entities.sort_by_key(|(entity, _)| entity.index);
+
+        let json = json!({
+            "version": VERSION,
+            "counter": generations.len(),
+            "generations": generations,
+            "resources": structs_to_json(resources)?,
+            "entities": entities.into_iter()
+                .map(|(entity, components)| Ok(json!({"id": entity.index, "components": structs_to_json(components)?})))
+                .collect::<Result<Vec<_>>>()?,
+        });
+
+        let json = serde_json::to_string_pretty(&json)?;
+        fs::write(path, json + "\n").map_err(|err| anyhow!("Could not write {}: {}.", path, err))
+    }
+
+    // Reads the snapshot at the given path, checking its values against the definitions.
+    pub fn load(path: &str, defs: &'static Map<Def>) -> Result<Snapshot> {
+        let json = fs::read_to_string(path).map_err(|err| anyhow!("Could not read {}: {}.", path, err))?;
+        Reader {defs}.snapshot(&json).map_err(|err| anyhow!("Invalid snapshot {}: {:#}", path, err))
+    }
+}
+
+// Reads the values of a snapshot, checking them against the definitions.
+struct Reader {
+    defs: &'static Map<Def>,
+}
+
+impl Reader {
+    // Reads a snapshot.
+    fn snapshot(&self, json: &str) -> Result<Snapshot> {
+        let json = serde_json::from_str::<Json>(json).map_err(|err| anyhow!("Invalid JSON: {}.", err))?;
+
+        match json["version"].as_u64() {
+            Some(1 | VERSION) => (),
+            Some(version) => return Err(anyhow!("Unsupported version {}, expected {}.", version, VERSION)),
+            None => return Err(anyhow!("Missing version.")),
+        }
+
+        let counter = json["counter"].as_u64().ok_or_else(|| anyhow!("Missing entity counter."))?;
+        let generations = match &json["generations"] {
+            // The first version gave IDs from the counter without reusing them, so the slots after
+            // the highest ID are free either way: only the ones up to it are created, rather than
+            // trusting the counter with the size of the allocation.
+            Json::Null => {
+                let slots = json["entities"].as_array().into_iter().flatten()
+                    .filter_map(|json| json["id"].as_u64())
+                    .filter(|&index| index < counter)
+                    .max()
+                    .map_or(0, |index| index + 1);
+                let mut generations = Vec::new();
+                generations.try_reserve_exact(slots as usize)
+                    .map_err(|_| anyhow!("Entity {} should be below the entity counter {}.", slots - 1, counter))?;
+                generations.resize(slots as usize, 0);
+                generations
+            }
+            json => json.as_array()
+                .filter(|generations| generations.len() as u64 == counter)
+                .and_then(|generations| generations.iter().map(as_generation).collect::<Option<Vec<_>>>())
+                .ok_or_else(|| anyhow!("Expected {} generations, got {}.", counter, json))?,
+        };
+
+        let mut resources = Map::new();
+        for (name, fields) in get_object(&json["resources"], "resources")? {
+            let var = self.struct_from_json(name, fields, "resource")?;
+            resources.insert(var.struct_type()?, var);
+        }
+
+        let mut entities = HashMap::new();
+        for json in json["entities"].as_array().ok_or_else(|| anyhow!("Missing entities."))? {
+            let index = json["id"].as_u64().ok_or_else(|| anyhow!("Missing entity id."))?;
+            if index >= counter {
+                return Err(anyhow!("Entity {} should be below the entity counter {}.", index, counter));
+            }
+            let entity = Entity {index, generation: generations[index as usize]};
+
+            let mut components = Map::new();
+            for (name, fields) in get_object(&json["components"], "components")? {
+                let var = self.struct_from_json(name, fields, "component")
+                    .map_err(|err| anyhow!("{}: {:#}", entity, err))?;
+                components.insert(var.struct_type()?, var);
+            }
+
+            if entities.insert(entity.clone(), components).is_some() {
+                return Err(anyhow!("{} appears twice.", entity));
+            }
+        }
+
+        Ok(Snapshot {generations, resources, entities})
+    }
+
+    // Reads a struct of the given type from its fields. The type must be of the given kind:
+    // a component, a resource, or any struct-like type for a struct.
+    fn struct_from_json(&self, name: &str, fields: &Json, kind: &str) -> Result<Var> {
+        let (name, def) = match (self.defs.get_key_value(name), kind) {
+            (Some((name, Def::Component(def))), "component" | "struct")
+            | (Some((name, Def::Resource(def))), "resource" | "struct")
+            | (Some((name, Def::Event(def) | Def::Struct(def))), "struct") => (*name, def),
+            _ => return Err(anyhow!("{} is not a {}.", name, kind)),
+        };
+
+        let fields = get_object(fields, "fields")?;
+        if def.fields.len() != fields.len() {
+            return Err(anyhow!("{} has {} fields, but {} fields were given.", name, def.fields.len(), fields.len()));
+        }
+
+        let mut map = Map::with_capacity(fields.len());
+        for (field, json) in fields {
+            let (field, ty) = def.fields.get_key_value(field.as_str())
+                .ok_or_else(|| anyhow!("{} is not a field of {}.", field, name))?;
+            let var = self.var_from_json(json)?;
+            check_field(name, field, ty, &var)?;
+            map.insert(*field, var);
+        }
+
+        Ok(Var::Struct(as_shared(Struct {name, map})))
+    }
+
+    // Reads a value.
+    fn var_from_json(&self, json: &Json) -> Result<Var> {
+        Ok(match json {
+            Json::Null => Var::Void,
+            Json::Bool(b) => Var::Bool(*b),
+            Json::Number(n) => match (n.as_i64(), n.as_f64()) {
+                (Some(i), _) if !n.is_f64() => Var::Int(i),
+                (_, Some(x)) => Var::Float(x),
+                _ => return Err(anyhow!("Invalid number {}.", n)),
+            },
+            Json::String(s) => Var::String(s.clone()),
+            Json::Array(list) => Var::List(as_shared(list.iter().map(|json| self.var_from_json(json)).collect::<Result<_>>()?)),
+            Json::Object(object) => {
+                if let Some(c) = object.get("char") {
+                    let mut chars = c.as_str().unwrap_or_default().chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Var::Char(c),
+                        _ => return Err(anyhow!("Invalid char {}.", json)),
+                    }
+                } else if let Some(index) = object.get("entity") {
+                    let generation = match object.get("generation") {
+                        Some(generation) => as_generation(generation),
+                        None => Some(0),
+                    };
+                    match (index.as_u64(), generation) {
+                        (Some(index), Some(generation)) => Var::Entity(Entity {index, generation}),
+                        _ => return Err(anyhow!("Invalid entity {}.", json)),
+                    }
+                } else if let Some(name) = object.get("struct") {
+                    let name = name.as_str().ok_or_else(|| anyhow!("Invalid struct {}.", json))?;
+                    self.struct_from_json(name, &json["fields"], "struct")?
+                } else if let Some(name) = object.get("enum") {
+                    self.enum_from_json(name, json)?
+                } else {
+                    return Err(anyhow!("Invalid value {}.", json));
+                }
+            }
+        })
+    }
+
+    // Reads an enum value.
+    fn enum_from_json(&self, name: &Json, json: &Json) -> Result<Var> {
+        let name = name.as_str().unwrap_or_default();
+        let (name, def) = match self.defs.get_key_value(name) {
+            Some((name, Def::Enum(def))) => (*name, def),
+            _ => return Err(anyhow!("{} is not an enum.", name)),
+        };
+
+        let variant = json["variant"].as_str().unwrap_or_default();
+        let variant = def.variant(variant).ok_or_else(|| anyhow!("{} is not a variant of {}.", variant, name))?;
+
+        let args = json["args"].as_array().ok_or_else(|| anyhow!("Invalid enum {}.", json))?;
+        if variant.fields.len() != args.len() {
+            return Err(anyhow!("{}::{} holds {} values, but {} were given.", name, variant.name, variant.fields.len(), args.len()));
+        }
+
+        let mut vars = Vec::with_capacity(args.len());
+        for (ty, json) in variant.fields.iter().zip(args) {
+            let var = self.var_from_json(json)?;
+            check_payload(name, variant.name, ty, &var)?;
+            vars.push(var);
+        }
+
+        Ok(Var::Enum(Rc::new(Enum {name, variant: variant.name, args: vars})))
+    }
+}
+
+// Converts a value to JSON. Functions can't be converted.
+fn to_json(var: &Var) -> Result<Json> {
+    Ok(match var {
+        Var::Void => Json::Null,
+        Var::Bool(b) => json!(b),
+        Var::Int(i) => json!(i),
+        Var::Float(x) if !x.is_finite() => return Err(anyhow!("{} cannot be saved.", var)),
+        Var::Float(x) => json!(x),
+        Var::Char(c) => json!({"char": c.to_string()}),
+        Var::String(s) => json!(s),
+        Var::Entity(e) => json!({"entity": e.index, "generation": e.generation}),
+        Var::List(list) => Json::Array(list.borrow().iter().map(to_json).collect::<Result<_>>()?),
+        Var::Struct(s) => json!({"struct": s.borrow().name, "fields": fields_to_json(&s.borrow().map)?}),
+        Var::Enum(e) => json!({"enum": e.name, "variant": e.variant, "args": e.args.iter().map(to_json).collect::<Result<Vec<_>>>()?}),
+        Var::Function(_) => return Err(anyhow!("{} cannot be saved.", var)),
+    })
+}
+
+// Converts the fields of a struct to a JSON object.
+fn fields_to_json(map: &Map<Var>) -> Result<Json> {
+    Ok(Json::Object(map.iter()
+        .map(|(name, var)| Ok((name.to_string(), to_json(var)?)))
+        .collect::<Result<_>>()?))
+}
+
+// Converts resources or components by name to a JSON object of their fields.
+fn structs_to_json(map: &Map<Var>) -> Result<Json> {
+    Ok(Json::Object(map.iter()
+        .map(|(name, var)| Ok((name.to_string(), match var {
+            Var::Struct(s) => fields_to_json(&s.borrow().map)?,
+            var => to_json(var)?,
+        })))
+        .collect::<Result<_>>()?))
+}
+
+// Gets the generation of an entity slot.
+fn as_generation(json: &Json) -> Option<u32> {
+    json.as_u64().and_then(|generation| u32::try_from(generation).ok())
+}
+
+// Gets the entries of a JSON object.
+fn get_object<'a>(json: &'a Json, what: &str) -> Result<&'a serde_json::Map<String, Json>> {
+    json.as_object().ok_or_else(|| anyhow!("Expected an object for the {}, got {}.", what, json))
+}
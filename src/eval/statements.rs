@@ -2,20 +2,20 @@ use super::*;
 
 // Evaluates a statement.
 pub fn eval_statement(ctx: &Context, scope: &Scope, stmt: &'static ast::Statement) -> Result<Flow> {
-    match stmt {
-        ast::Statement::Break => Ok(Flow::Break),
-        ast::Statement::Continue => Ok(Flow::Continue),
-        ast::Statement::Return(Some(expr)) => Ok(Flow::Return(eval_expr(ctx, scope, expr)?)),
-        ast::Statement::Return(None) => Ok(Flow::Return(Var::Void)),
-        ast::Statement::Expr(expr) => eval_expr(ctx, scope, expr).map(|_| Flow::Ok),
-        ast::Statement::If(if_) => eval_if(ctx, scope, if_),
-        ast::Statement::Block(block) => eval_block(ctx, scope, block),
-        ast::Statement::Decl(decl) => eval_decl(ctx, scope, decl),
-        ast::Statement::For(for_) => eval_for(ctx, scope, for_),
-        ast::Statement::While(while_) => eval_while(ctx, scope, while_),
-        ast::Statement::Query(query) => eval_query(ctx, scope, query),
-        ast::Statement::Switch(switch) => eval_switch(ctx, scope, switch),
-    }
+    match &stmt.kind {
+        ast::StatementKind::Break => Ok(Flow::Break),
+        ast::StatementKind::Continue => Ok(Flow::Continue),
+        ast::StatementKind::Return(Some(expr)) => Ok(Flow::Return(eval_expr(ctx, scope, expr)?)),
+        ast::StatementKind::Return(None) => Ok(Flow::Return(Var::Void)),
+        ast::StatementKind::Expr(expr) => eval_expr(ctx, scope, expr).map(|_| Flow::Ok),
+        ast::StatementKind::If(if_) => eval_if(ctx, scope, if_),
+        ast::StatementKind::Block(block) => eval_block(ctx, scope, block),
+        ast::StatementKind::Decl(decl) => eval_decl(ctx, scope, decl),
+        ast::StatementKind::For(for_) => eval_for(ctx, scope, for_),
+        ast::StatementKind::While(while_) => eval_while(ctx, scope, while_),
+        ast::StatementKind::Query(query) => eval_query(ctx, scope, query),
+        ast::StatementKind::Switch(switch) => eval_switch(ctx, scope, switch),
+    }.at(stmt.span)
 }
 
 // Evaluates a block of statements.
@@ -45,7 +45,7 @@ pub fn eval_if(ctx: &Context, scope: &Scope, if_: &'static ast::If) -> Result<Fl
                 Ok(Flow::Ok)
             }
         },
-        _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in an if statement.")),
+        _ => Err(anyhow!("A condition expression evaluated to a non-boolean value in an if statement.")).at(if_.cond.span),
     }
 }
 
@@ -54,15 +54,15 @@ pub fn eval_for(ctx: &Context, scope: &Scope, for_: &'static ast::For) -> Result
     scope.next();
 
     match &for_.init {
-        Either::Left(expr) => { eval_expr(ctx, scope, &expr)?; },
-        Either::Right(decl) => { eval_decl(ctx, scope, &decl)?; },
+        Either::Left(expr) => { eval_expr(ctx, scope, expr)?; },
+        Either::Right(decl) => { eval_decl(ctx, scope, decl)?; },
     };
     
     loop {
         match eval_expr(ctx, scope, &for_.cond)? {
             Var::Bool(true) => (),
             Var::Bool(false) => break,
-            _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in a for loop.")),
+            _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in a for loop.")).at(for_.cond.span),
         }
 
         match eval_block(ctx, scope, &for_.code)? {
@@ -84,7 +84,7 @@ pub fn eval_for(ctx: &Context, scope: &Scope, for_: &'static ast::For) -> Result
 // Evaluates a declaration.
 pub fn eval_decl(ctx: &Context, scope: &Scope, decl: &'static ast::Decl) -> Result<Flow> {
     match &decl.init {
-        Some(init) => scope.new_var(decl.ident, eval_expr(ctx, scope, &init)?),
+        Some(init) => scope.new_var(decl.ident, eval_expr(ctx, scope, init)?),
         _ => scope.new_var(decl.ident, Var::Void),
     };
     Ok(Flow::Ok)
@@ -98,7 +98,7 @@ pub fn eval_while(ctx: &Context, scope: &Scope, while_: &'static ast::While) ->
         match eval_expr(ctx, scope, &while_.cond)? {
             Var::Bool(true) => (),
             Var::Bool(false) => break,
-            _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in a while loop.")),
+            _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in a while loop.")).at(while_.cond.span),
         }
 
         match eval_block(ctx, scope, &while_.code)? {
@@ -121,12 +121,45 @@ pub fn eval_switch(ctx: &Context, scope: &Scope, switch: &'static ast::Switch) -
     let var = eval_expr(ctx, scope, &switch.expr)?;
 
     for case in &switch.cases {
-        if var == eval_atom(&case.val)? {
-            return eval_block(ctx, scope, &case.block);
+        match &case.pattern {
+            ast::Pattern::Atom(atom) => if var == eval_atom(atom)? {
+                return eval_block(ctx, scope, &case.block);
+            },
+            ast::Pattern::Variant(pattern) => match &var {
+                Var::Enum(e) if e.name == pattern.name && e.variant == pattern.variant => {
+                    return eval_variant_case(ctx, scope, case, pattern, e).at(case.span);
+                }
+                _ => (),
+            },
         }
     }
 
-    eval_block(ctx, scope, &switch.default)
+    match &switch.default {
+        Some(block) => eval_block(ctx, scope, block),
+        None => Ok(Flow::Ok),
+    }
+}
+
+// Evaluates the block of a case matching a variant, with the values it holds bound to its variables.
+fn eval_variant_case(
+    ctx: &Context, 
+    scope: &Scope, 
+    case: &'static ast::SwitchCase, 
+    pattern: &'static ast::VariantPattern, 
+    e: &Enum,
+) -> Result<Flow> {
+    if pattern.bindings.len() != e.args.len() {
+        return Err(anyhow!("{}::{} holds {} values, but {} variables were given.", e.name, e.variant, e.args.len(), pattern.bindings.len()));
+    }
+
+    scope.next();
+    for (name, var) in pattern.bindings.iter().zip(e.args.iter()) {
+        scope.new_var(name, var.clone());
+    }
+    let flow = eval_block(ctx, scope, &case.block);
+    scope.prev();
+
+    flow
 }
 
 pub fn eval_query(ctx: &Context, scope: &Scope, query: &'static ast::Query) -> Result<Flow> {
@@ -142,16 +175,11 @@ pub fn eval_query(ctx: &Context, scope: &Scope, query: &'static ast::Query) -> R
 
     // Evaluates the code for each entity.
     for entity in matches.iter() {
-        // Put the entity into the scope.
-        scope.new_var(filter.name, Var::Entity(entity.clone()));
+        // Put the entity and its components into the scope.
+        bind_entity(ctx, scope, filter, entity)?;
 
-        // Adds all components to the scope.
-        for arg in filter.args.iter() {
-            scope.new_var(arg.name, ctx.world().get_component(entity.clone(), arg.ty)?);
-        }
-        
         // Evaluates the code.
-        let ret = eval_block(ctx, &scope, &query.code)?;
+        let ret = eval_block(ctx, scope, &query.code)?;
         if matches!(ret, Flow::Break | Flow::Return(_)) {
             break;
         }
@@ -2,28 +2,66 @@ use super::*;
 
 // Evaluates a system.
 pub fn eval_system(ctx: &Context, sys: &'static ast::System) -> Result<()> {
+    eval_system_inner(ctx, sys).at(sys.span)
+}
+
+// Puts the entity matched by a filter into the scope, along with its components.
+// Optional components the entity doesn't have are bound to void.
+pub fn bind_entity(ctx: &Context, scope: &Scope, filter: &ast::EntityFilter, entity: &Entity) -> Result<()> {
+    let world = ctx.world();
+
+    scope.new_var(filter.name, Var::Entity(entity.clone()));
+
+    for arg in filter.args.iter() {
+        scope.new_var(arg.name, world.get_component(entity.clone(), arg.ty)?);
+    }
+
+    for arg in filter.optional.iter() {
+        scope.new_var(arg.name, world.get_optional_component(entity.clone(), arg.ty)?);
+    }
+
+    Ok(())
+}
+
+// Evaluates a system, without locating the errors.
+fn eval_system_inner(ctx: &Context, sys: &'static ast::System) -> Result<()> {
+    // Skips the system if one of the resources it needs doesn't exist.
+    for arg in &sys.filter.resources {
+        if !ctx.world().has_resource(arg.ty)? {
+            return Ok(());
+        }
+    }
+
+    // Reads the events once, for all the entities, each getting its own copy. They are only read
+    // when the code runs, so that they are still there the next time if no entity matches.
+    let read_events = || sys.filter.events.iter()
+        .map(|arg| (arg.name, ctx.read_events(arg.ty)))
+        .collect::<Vec<_>>();
+
     // Creates the system's scope.
     let scope = Scope::default();
 
     if let Some(filter) = &sys.filter.entities {
         // Get the entities matches.
         let matches = ctx.world_mut().filter_entities(filter)?;
+        let events = match matches.is_empty() {
+            true => Vec::new(),
+            false => read_events(),
+        };
 
         // Evaluates the code for each entity.
         for entity in matches.iter() {
-            // Put the entity into the scope.
-            scope.new_var(filter.name, Var::Entity(entity.clone()));
-
-            // Put the resources in scope.
+            // Put the resources and the events in scope.
             for arg in &sys.filter.resources {
                 scope.new_var(arg.name, ctx.world().get_resource(arg.ty)?);
             }
-
-            // Adds all components to the scope.
-            for arg in filter.args.iter() {
-                scope.new_var(arg.name, ctx.world().get_component(entity.clone(), arg.ty)?);
+            for (name, var) in &events {
+                scope.new_var(name, var.deep_copy());
             }
 
+            // Put the entity and its components into the scope.
+            bind_entity(ctx, &scope, filter, entity)?;
+
             // Evaluates the code.
             match eval_block(ctx, &scope, &sys.code)? {
                 Flow::Return(_) => return Err(anyhow!("Systems can't return.")),
@@ -32,20 +70,20 @@ pub fn eval_system(ctx: &Context, sys: &'static ast::System) -> Result<()> {
             };
         }
     } else {
-        // Put the resources in scope.
+        // Put the resources and the events in scope.
         for arg in &sys.filter.resources {
             scope.new_var(arg.name, ctx.world().get_resource(arg.ty)?);
         }
+        for (name, var) in read_events() {
+            scope.new_var(name, var);
+        }
 
         // If there are no entities matches, evaluates the code only once.
-        match eval_block(ctx, &scope, &sys.code)? {
-            Flow::Return(_) => return Err(anyhow!("Systems can't return.")),
-            _ => (),
-        };
+        if let Flow::Return(_) = eval_block(ctx, &scope, &sys.code)? {
+            return Err(anyhow!("Systems can't return."));
+        }
     }
 
     // Apply the commannds to the world.
-    ctx.update();
-
-    Ok(())
+    ctx.update()
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::rc::Rc;
 
 use super::*;
 
@@ -14,6 +15,8 @@ pub enum Var {
     Entity(ecs::Entity),
     List(Shared<Vec<Var>>),
     Struct(Shared<Struct>),
+    Enum(Rc<Enum>),
+    Function(Rc<Closure>),
 }
 
 // A struct's value.
@@ -23,6 +26,23 @@ pub struct Struct {
     pub map: Map<Var>,
 }
 
+// An enum's value. Enum values are immutable.
+#[derive(Debug)]
+pub struct Enum {
+    pub name: &'static str,
+    pub variant: &'static str,
+    pub args: Vec<Var>,
+}
+
+// A function value, either a named function or an anonymous one along with the
+// variables it captured, by value and sorted by name.
+#[derive(Debug)]
+pub struct Closure {
+    pub name: Option<&'static str>,
+    pub def: &'static ast::Function,
+    pub captures: Vec<(&'static str, Var)>,
+}
+
 impl Var {
     // Returns the variable's type.
     pub fn get_type(&self) -> ast::Type {
@@ -36,6 +56,27 @@ impl Var {
             Var::List(_) => ast::Type::List,
             Var::Entity(_) => ast::Type::Entity,
             Var::Struct(s) => ast::Type::Struct(s.borrow().name),
+            Var::Enum(e) => ast::Type::Struct(e.name),
+            Var::Function(_) => ast::Type::Function,
+        }
+    }
+
+    // Returns a copy of the variable that shares nothing with it, copying the lists and
+    // structs it holds. Functions are still shared, their captures can't be changed.
+    pub fn deep_copy(&self) -> Var {
+        match self {
+            Var::List(list) => Var::List(as_shared(list.borrow().iter().map(Var::deep_copy).collect())),
+            Var::Struct(s) => {
+                let s = s.borrow();
+                let map = s.map.iter().map(|(name, var)| (*name, var.deep_copy())).collect();
+                Var::Struct(as_shared(Struct {name: s.name, map}))
+            }
+            Var::Enum(e) => Var::Enum(Rc::new(Enum {
+                name: e.name,
+                variant: e.variant,
+                args: e.args.iter().map(Var::deep_copy).collect(),
+            })),
+            var => var.clone(),
         }
     }
 
@@ -74,7 +115,10 @@ impl fmt::Display for Var {
             Var::Struct(s) => {
                 write!(f, "{{")?;
                 let borrow = s.borrow();
-                let mut iter = borrow.map.iter();
+                // Fields are sorted by name, for the output not to depend on the map's order.
+                let mut fields = borrow.map.iter().collect::<Vec<_>>();
+                fields.sort_by_key(|(name, _)| **name);
+                let mut iter = fields.into_iter();
                 if let Some((name, var)) = iter.next() {
                     write!(f, "{}: {}", name, var)?;
                 }
@@ -83,6 +127,18 @@ impl fmt::Display for Var {
                 }
                 write!(f, "}}")
             },
+            Var::Enum(e) => {
+                write!(f, "{}::{}", e.name, e.variant)?;
+                if !e.args.is_empty() {
+                    let args = e.args.iter().map(Var::to_string).collect::<Vec<_>>();
+                    write!(f, "({})", args.join(", "))?;
+                }
+                Ok(())
+            },
+            Var::Function(closure) => match closure.name {
+                Some(name) => write!(f, "function {}({})", name, closure.def.args.join(", ")),
+                None => write!(f, "function({})", closure.def.args.join(", ")),
+            },
         }
     }
 }
@@ -91,6 +147,7 @@ impl PartialEq for Var {
     // Compares two variables.
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Var::Void, Var::Void) => true,
             (Var::Bool(l), Var::Bool(r)) => l == r,
             (Var::Int(l), Var::Int(r)) => l == r,
             (Var::Float(l), Var::Float(r)) => l == r,
@@ -98,6 +155,8 @@ impl PartialEq for Var {
             (Var::String(l), Var::String(r)) => l == r,
             (Var::Entity(l), Var::Entity(r)) => l == r,
             (Var::Struct(l), Self::Struct(r)) => l.borrow().name == r.borrow().name,
+            (Var::Enum(l), Var::Enum(r)) => l.name == r.name && l.variant == r.variant && l.args == r.args,
+            (Var::Function(l), Var::Function(r)) => std::ptr::eq(l.def, r.def) && l.captures == r.captures,
             _ => false,
         }
     }
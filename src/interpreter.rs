@@ -0,0 +1,490 @@
+use anyhow::{anyhow, Error, Result};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::ast;
+use crate::check;
+use crate::eval::{self, Closure, Context, Def, Entity, Enum, Native, Struct, Var};
+use crate::parser;
+use crate::utils::*;
+use crate::vm;
+
+/// A C* value, copied out of the interpreter.
+///
+/// Contrary to the values manipulated by scripts, lists and structs are
+/// deep copies: mutating a `Value` has no effect on the world it was read from.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Void,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Char(char),
+    String(String),
+    Entity(Entity),
+    List(Vec<Value>),
+    Struct {
+        name: String,
+        fields: HashMap<String, Value>,
+    },
+    Enum {
+        name: String,
+        variant: String,
+        args: Vec<Value>,
+    },
+    Function(Function),
+}
+
+/// A function value of a script, like `function(x) { return 2 * x; }`. It is
+/// opaque to the host, but can be given back to the interpreter it was read from.
+#[derive(Clone, Debug)]
+pub struct Function(Rc<Closure>);
+
+impl PartialEq for Function {
+    // Functions are equal if they have the same definition and captured the same values.
+    fn eq(&self, other: &Self) -> bool {
+        Var::Function(self.0.clone()) == Var::Function(other.0.clone())
+    }
+}
+
+impl Value {
+    /// Returns the field with the given name, if the value is a struct that has one.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct {fields, ..} => fields.get(name),
+            _ => None,
+        }
+    }
+
+    // Converts the value back into a variable of the given context, checking struct types.
+    fn into_var(self, ctx: &Context) -> Result<Var> {
+        Ok(match self {
+            Value::Void => Var::Void,
+            Value::Bool(b) => Var::Bool(b),
+            Value::Int(i) => Var::Int(i),
+            Value::Float(x) => Var::Float(x),
+            Value::Char(c) => Var::Char(c),
+            Value::String(s) => Var::String(s),
+            Value::Entity(e) => Var::Entity(e),
+            Value::List(list) => Var::List(as_shared(list.into_iter()
+                .map(|val| val.into_var(ctx))
+                .collect::<Result<_>>()?)),
+            Value::Struct {name, fields} => {
+                let (name, def) = match ctx.get_def_entry(&name)? {
+                    (name, Def::Component(def) | Def::Resource(def) | Def::Event(def) | Def::Struct(def)) => (name, def),
+                    _ => return Err(anyhow!("{} is not a struct type.", name)),
+                };
+
+                if def.fields.len() != fields.len() {
+                    return Err(anyhow!("{} has {} fields, but {} fields were given.", name, def.fields.len(), fields.len()));
+                }
+
+                let mut map = Map::with_capacity(fields.len());
+                for (field, val) in fields {
+                    let (field, ty) = def.fields.get_key_value(field.as_str())
+                        .ok_or_else(|| anyhow!("{} is not a field of {}.", field, name))?;
+                    let var = val.into_var(ctx)?;
+                    if var.get_type() != *ty {
+                        return Err(anyhow!("Field {} of {} should be of type {}, but {} was given.", field, name, ty, var));
+                    }
+                    map.insert(*field, var);
+                }
+
+                Var::Struct(as_shared(Struct {name, map}))
+            }
+            Value::Enum {name, variant, args} => {
+                let (name, def) = match ctx.get_def_entry(&name)? {
+                    (name, Def::Enum(def)) => (name, def),
+                    _ => return Err(anyhow!("{} is not an enum.", name)),
+                };
+
+                let variant = def.variant(&variant)
+                    .ok_or_else(|| anyhow!("{} is not a variant of {}.", variant, name))?;
+                if variant.fields.len() != args.len() {
+                    return Err(anyhow!("{}::{} holds {} values, but {} were given.", name, variant.name, variant.fields.len(), args.len()));
+                }
+
+                let mut vars = Vec::with_capacity(args.len());
+                for (ty, val) in variant.fields.iter().zip(args) {
+                    let var = val.into_var(ctx)?;
+                    if var.get_type() != *ty {
+                        return Err(anyhow!("{}::{} should hold a value of type {}, but {} was given.", name, variant.name, ty, var));
+                    }
+                    vars.push(var);
+                }
+
+                Var::Enum(Rc::new(Enum {name, variant: variant.name, args: vars}))
+            }
+            Value::Function(Function(closure)) => Var::Function(closure),
+        })
+    }
+}
+
+impl From<&Var> for Value {
+    fn from(var: &Var) -> Self {
+        match var {
+            Var::Void => Value::Void,
+            Var::Bool(b) => Value::Bool(*b),
+            Var::Int(i) => Value::Int(*i),
+            Var::Float(x) => Value::Float(*x),
+            Var::Char(c) => Value::Char(*c),
+            Var::String(s) => Value::String(s.clone()),
+            Var::Entity(e) => Value::Entity(e.clone()),
+            Var::List(list) => Value::List(list.borrow().iter().map(Value::from).collect()),
+            Var::Struct(s) => {
+                let s = s.borrow();
+                Value::Struct {
+                    name: s.name.to_string(),
+                    fields: s.map.iter().map(|(name, var)| (name.to_string(), var.into())).collect(),
+                }
+            }
+            Var::Enum(e) => Value::Enum {
+                name: e.name.to_string(),
+                variant: e.variant.to_string(),
+                args: e.args.iter().map(Value::from).collect(),
+            },
+            Var::Function(closure) => Value::Function(Function(closure.clone())),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    // Values are printed the same way scripts print them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Void => write!(f, "void"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Entity(e) => write!(f, "{}", e),
+            Value::List(list) => {
+                write!(f, "[")?;
+                for (i, val) in list.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", val)?;
+                }
+                write!(f, "]")
+            }
+            Value::Struct {fields, ..} => {
+                write!(f, "{{")?;
+                let mut fields = fields.iter().collect::<Vec<_>>();
+                fields.sort_by_key(|(name, _)| *name);
+                for (i, (name, val)) in fields.into_iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, val)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Enum {name, variant, args} => {
+                write!(f, "{}::{}", name, variant)?;
+                if !args.is_empty() {
+                    let args = args.iter().map(Value::to_string).collect::<Vec<_>>();
+                    write!(f, "({})", args.join(", "))?;
+                }
+                Ok(())
+            }
+            Value::Function(Function(closure)) => write!(f, "{}", Var::Function(closure.clone())),
+        }
+    }
+}
+
+/// An instance of a C* program, along with its world.
+///
+/// Loading a program only parses it: no system is run until [`Interpreter::init`],
+/// [`Interpreter::step`] or [`Interpreter::run`] is called. The program is checked
+/// right before its first system runs, so that the functions registered by the host
+/// are known by then.
+///
+/// Systems and queries visit the entities matching their filter by the index of their
+/// slot, so that running a program twice with the same inputs gives the same results.
+/// An entity spawned in the slot of a deleted one is visited before the ones spawned
+/// in later slots.
+///
+/// Scripts may call `Exit(code)` to stop the program: the current system still
+/// runs to completion and its commands are applied, but no other `Init` or `Run`
+/// system is started afterwards.
+///
+/// Programs are run by a tree-walking evaluator, or by a bytecode VM if
+/// [`Interpreter::use_vm`] is called. Both give the same results.
+pub struct Interpreter {
+    ast: &'static ast::AST,
+    ctx: Context,
+    checked: bool,
+    warnings: Vec<Error>,
+    use_vm: bool,
+    // The compiled program, once the VM is used.
+    program: Option<vm::Program>,
+}
+
+impl Interpreter {
+    /// Loads the program at the given path, along with the modules it includes.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Interpreter> {
+        Interpreter::new(parser::parse_program(path.as_ref())?)
+    }
+
+    /// Loads a program from its source code. Includes are resolved relatively
+    /// to the current working directory.
+    pub fn from_source(code: &str) -> Result<Interpreter> {
+        Interpreter::new(parser::parse_source(Path::new("<source>"), code.to_string())?)
+    }
+
+    // Creates the interpreter of the given AST.
+    fn new(ast: &'static ast::AST) -> Result<Interpreter> {
+        Ok(Interpreter {
+            ast,
+            ctx: Context::new(eval::definitions(ast)?),
+            checked: false,
+            warnings: Vec::new(),
+            use_vm: false,
+            program: None,
+        })
+    }
+
+    /// Returns the syntax tree of the program.
+    pub fn ast(&self) -> &'static ast::AST {
+        self.ast
+    }
+
+    /// Checks the program for errors that can be found without running it, such as
+    /// struct initializers with fields of the wrong type, filters with unknown components
+    /// or calls with the wrong number of arguments. All the errors found are reported at once.
+    ///
+    /// This is done automatically before the first system runs, and only once.
+    /// Problems that don't prevent the program from running, like a switch over an enum
+    /// that doesn't handle all of its variants, are reported by [`Interpreter::warnings`].
+    pub fn check(&mut self) -> Result<()> {
+        if !self.checked {
+            self.warnings.clear();
+            check::check(self.ast, &self.ctx, &mut self.warnings)?;
+            self.checked = true;
+        }
+        Ok(())
+    }
+
+    /// Returns the warnings found when checking the program.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Selects whether systems are run by the bytecode VM rather than by the
+    /// tree-walking evaluator. The program is compiled once checked, before the
+    /// next system runs.
+    pub fn use_vm(&mut self, enabled: bool) {
+        self.use_vm = enabled;
+        if !enabled {
+            self.program = None;
+        }
+    }
+
+    // Checks the program, and compiles it if the VM is used.
+    fn prepare(&mut self) -> Result<()> {
+        self.check()?;
+        if self.use_vm && self.program.is_none() {
+            self.program = Some(vm::compile(self.ast, &self.ctx)?);
+        }
+        Ok(())
+    }
+
+    // Runs a system by its name, with the selected backend.
+    fn run_one(&self, name: &str) -> Result<()> {
+        match &self.program {
+            Some(program) => program.run_system(&self.ctx, name),
+            None => eval::run_system(&self.ctx, name),
+        }
+    }
+
+    /// Runs the systems of the `Init` list, once each and in order.
+    pub fn init(&mut self) -> Result<()> {
+        self.prepare()?;
+        self.run_systems(&self.ast.init)
+    }
+
+    /// Runs the systems of the `Run` list, in order, the given number of times.
+    /// Stops early if a script requested to exit.
+    pub fn step(&mut self, times: usize) -> Result<()> {
+        self.prepare()?;
+        for _ in 0..times {
+            self.ctx.next_step();
+            self.run_systems(&self.ast.run)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the systems of the `Shutdown` list, once each and in order, even if a script
+    /// requested to exit.
+    pub fn shutdown(&mut self) -> Result<()> {
+        self.prepare()?;
+        for name in self.ast.shutdown.iter() {
+            self.run_one(name)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the exit code requested by a script with `Exit(code)`, if any.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.ctx.exit_code()
+    }
+
+    // Runs the given systems in order, stopping when a script requested to exit.
+    fn run_systems(&self, names: &[&'static str]) -> Result<()> {
+        for name in names {
+            if self.exit_code().is_some() {
+                break;
+            }
+            self.run_one(name)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the system with the given name once.
+    pub fn run_system(&mut self, name: &str) -> Result<()> {
+        self.prepare()?;
+        self.run_one(name)
+    }
+
+    /// Registers a Rust function that scripts can call by its name, taking
+    /// precedence over builtins and functions defined in C*.
+    ///
+    /// Calls are checked to have as many arguments as there are parameters, and
+    /// each argument must be of the type of its parameter, `None` accepting any type.
+    /// The arguments are copies: mutating them has no effect on the script's values.
+    ///
+    /// ```
+    /// use cstar::{ast::Type, Interpreter, Value};
+    ///
+    /// let mut interpreter = Interpreter::from_source(r#"
+    ///     System main() {
+    ///         println(hypot(3.0, 4.0));
+    ///     }
+    ///
+    ///     Init [main];
+    ///     Run [];
+    /// "#)?;
+    ///
+    /// interpreter.register_fn("hypot", &[Some(Type::Float), Some(Type::Float)], |args| {
+    ///     match args {
+    ///         [Value::Float(x), Value::Float(y)] => Ok(Value::Float(x.hypot(*y))),
+    ///         _ => unreachable!(),
+    ///     }
+    /// });
+    ///
+    /// interpreter.run()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn register_fn(
+        &mut self, 
+        name: &str, 
+        params: &[Option<ast::Type>], 
+        fun: impl Fn(&[Value]) -> Result<Value> + 'static,
+    ) {
+        self.ctx.register_native(name, Native::new(params, host_fn(fun)));
+        self.program = None;
+        self.checked = false;
+    }
+
+    /// Registers a Rust function that accepts any number of arguments of any type.
+    /// See [`Interpreter::register_fn`].
+    pub fn register_variadic_fn(&mut self, name: &str, fun: impl Fn(&[Value]) -> Result<Value> + 'static) {
+        self.ctx.register_native(name, Native::variadic(host_fn(fun)));
+        self.program = None;
+        self.checked = false;
+    }
+
+    /// Runs the `Init` systems, then the `Run` systems in a loop until a script
+    /// requests to exit, and finally the `Shutdown` systems. Returns the exit code,
+    /// which is 0 if the `Run` list is empty and no script called `Exit`.
+    pub fn run(&mut self) -> Result<i32> {
+        self.init()?;
+
+        // Loops only if there are systems to run.
+        if !self.ast.run.is_empty() {
+            while self.exit_code().is_none() {
+                self.step(1)?;
+            }
+        }
+
+        self.shutdown()?;
+        Ok(self.exit_code().unwrap_or(0))
+    }
+
+    /// Writes the entities, their components and the resources to the file at
+    /// the given path, as JSON. The format is described in the README.
+    ///
+    /// ```
+    /// use cstar::{Interpreter, Value};
+    ///
+    /// let source = r#"
+    ///     Component Name { string value; }
+    ///
+    ///     System spawn() {
+    ///         Spawn(Name { value: "Alice"; });
+    ///     }
+    ///
+    ///     Init [spawn];
+    ///     Run [];
+    /// "#;
+    /// let path = std::env::temp_dir().join("cstar-save-world.json");
+    /// let path = path.to_str().unwrap();
+    ///
+    /// let mut interpreter = Interpreter::from_source(source)?;
+    /// interpreter.run()?;
+    /// interpreter.save_world(path)?;
+    ///
+    /// let mut restored = Interpreter::from_source(source)?;
+    /// restored.load_world(path)?;
+    /// let alice = &restored.entities()[0];
+    /// assert_eq!(restored.component(alice, "Name")?.field("value"), Some(&Value::String("Alice".into())));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn save_world(&self, path: &str) -> Result<()> {
+        self.ctx.world().save(path)
+    }
+
+    /// Replaces the entities, their components and the resources with the ones saved
+    /// in the file at the given path. The snapshot is checked against the types of the
+    /// program, and the world is left untouched if it doesn't match them.
+    pub fn load_world(&mut self, path: &str) -> Result<()> {
+        let snapshot = self.ctx.world().load(path)?;
+        self.ctx.world_mut().restore(snapshot);
+        Ok(())
+    }
+
+    /// Returns a copy of the resource with the given name.
+    pub fn resource(&self, name: &str) -> Result<Value> {
+        Ok((&self.ctx.world().get_resource(name)?).into())
+    }
+
+    /// Returns all the entities currently alive, sorted by ID.
+    pub fn entities(&self) -> Vec<Entity> {
+        self.ctx.world().entities()
+    }
+
+    /// Returns a copy of the component with the given name of an entity.
+    pub fn component(&self, entity: &Entity, name: &str) -> Result<Value> {
+        Ok((&self.ctx.world().get_component(entity.clone(), name)?).into())
+    }
+
+    /// Returns a copy of all the components of an entity, by name.
+    pub fn components(&self, entity: &Entity) -> Result<HashMap<String, Value>> {
+        Ok(self.ctx.world().get_components(entity)?.iter()
+            .map(|(name, var)| (name.to_string(), var.into()))
+            .collect())
+    }
+}
+
+// Wraps a function working on values into one working on variables.
+fn host_fn(fun: impl Fn(&[Value]) -> Result<Value>) -> impl Fn(&Context, Vec<Var>) -> Result<Var> {
+    move |ctx, args| {
+        let args = args.iter().map(Value::from).collect::<Vec<_>>();
+        fun(&args)?.into_var(ctx)
+    }
+}
@@ -0,0 +1,52 @@
+//! An interpreter for C*, an ECS-based C-style language.
+//!
+//! The [`Interpreter`] type loads a program and runs its systems, while
+//! giving access to the world's resources and entities as [`Value`]s:
+//!
+//! ```
+//! use cstar::{Interpreter, Value};
+//!
+//! let mut interpreter = Interpreter::from_source(r#"
+//!     Resource Counter {
+//!         int value;
+//!     }
+//!
+//!     System init() {
+//!         NewResource(Counter { value: 0; });
+//!     }
+//!
+//!     System count(Counter c) {
+//!         c.value = c.value + 1;
+//!     }
+//!
+//!     Init [init];
+//!     Run [count];
+//! "#)?;
+//!
+//! interpreter.init()?;
+//! interpreter.step(3)?;
+//!
+//! let counter = interpreter.resource("Counter")?;
+//! assert_eq!(counter.field("value"), Some(&Value::Int(3)));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+#![allow(unused)]
+#![allow(clippy::upper_case_acronyms, clippy::large_enum_variant)]
+
+pub mod ast;
+mod check;
+mod eval;
+mod interpreter;
+mod lsp;
+mod parser;
+mod repl;
+mod sources;
+mod utils;
+mod vm;
+
+pub use eval::Entity;
+pub use interpreter::{Function, Interpreter, Value};
+pub use lsp::LanguageServer;
+pub use parser::format;
+pub use repl::Repl;
@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+use crate::ast::{self, Name, Span, StructDef};
+use crate::parser;
+use crate::sources::SpanError;
+
+use super::*;
+
+// A source file opened in the editor.
+pub struct Document {
+    uri: String,
+    path: PathBuf,
+    text: String,
+    // The syntax tree of the last version of the text without errors, so that
+    // names can still be found while an edit is in progress.
+    ast: Option<&'static ast::AST>,
+    // The error found when parsing the current version of the text, if any.
+    error: Option<Error>,
+}
+
+impl Document {
+    // Creates a document, parsing its text.
+    pub fn new(uri: &str, text: String) -> Document {
+        let mut document = Document {
+            uri: uri.to_string(),
+            path: path_of_uri(uri),
+            text: String::new(),
+            ast: None,
+            error: None,
+        };
+        document.update(text);
+        document
+    }
+
+    // Replaces the text of the document, parsing it again. The parser leaks the code it reads
+    // and the syntax tree it builds, so a text is only parsed when it differs from the last one,
+    // and the included files that didn't change are kept once: the memory used grows with the
+    // versions of the document edited in the session, not with the requests.
+    pub fn update(&mut self, text: String) {
+        if self.text == text && (self.ast.is_some() || self.error.is_some()) {
+            return;
+        }
+        match parser::parse_document(&self.path, text.clone()) {
+            Ok(ast) => {
+                self.ast = Some(ast);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+        self.text = text;
+    }
+
+    // Returns the text of the document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // Returns the diagnostics of the current version of the text.
+    pub fn diagnostics(&self) -> Vec<Json> {
+        let err = match &self.error {
+            Some(err) => err,
+            None => return Vec::new(),
+        };
+
+        // Errors located in an included file are shown at the top of the document.
+        let (range, message) = match err.downcast_ref::<SpanError>() {
+            Some(SpanError {span, error}) if self.is_in(span) => (range(span.source(), span.offsets()), format!("{:#}", error)),
+            Some(SpanError {span, error}) => (range(&self.text, (0, 0)), format!("{:#} ({})", error, span)),
+            None => (range(&self.text, (0, 0)), format!("{:#}", err)),
+        };
+
+        vec![json!({"range": range, "severity": 1, "source": "cstar", "message": message})]
+    }
+
+    // Returns the location of the definition of the name at the given offset.
+    pub fn definition(&self, offset: usize) -> Option<Json> {
+        let (_, span) = self.name_at(offset)?;
+        Some(json!({"uri": self.uri_of(&span), "range": range(span.source(), span.offsets())}))
+    }
+
+    // Returns the definition of the name at the given offset, as a code block after its doc
+    // comment if it has one. The bodies of functions and systems are left out.
+    pub fn hover(&self, offset: usize) -> Option<Json> {
+        let (name, span) = self.name_at(offset)?;
+        let ast = self.ast?;
+        let code = match ast.names.get(name)? {
+            Name::Function(_) | Name::System(_) => span.as_str().split('{').next().unwrap().trim_end(),
+            _ => span.as_str(),
+        };
+
+        let mut value = format!("```cstar\n{}\n```", code);
+        if let Some(doc) = ast.docs.get(name) {
+            value = format!("{}\n\n{}", doc, value);
+        }
+
+        Some(json!({
+            "contents": {"kind": "markdown", "value": value},
+            "range": range(&self.text, word_at(&self.text, offset)?),
+        }))
+    }
+
+    // Returns the fields that can follow the `.` before the given offset, in the order
+    // they are declared. The type of the variable before the `.` is found from the
+    // closest argument of a system or a query declaring it.
+    pub fn completions(&self, offset: usize) -> Vec<Json> {
+        let def = match self.struct_before(offset) {
+            Some(def) => def,
+            None => return Vec::new(),
+        };
+
+        let mut fields = def.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|(name, _)| def.spans[*name].offsets());
+
+        fields.into_iter()
+            .map(|(name, ty)| json!({"label": name, "kind": FIELD, "detail": ty.to_string()}))
+            .collect()
+    }
+
+    // Returns the name at the given offset and the location of its definition, if it has one.
+    fn name_at(&self, offset: usize) -> Option<(&'static str, Span)> {
+        let (start, end) = word_at(&self.text, offset)?;
+        let (name, span) = self.ast?.spans.get_key_value(&self.text[start..end])?;
+        Some((*name, *span))
+    }
+
+    // Returns the definition of the struct-like type of the path of fields ending with a `.` before the offset.
+    fn struct_before(&self, offset: usize) -> Option<&'static StructDef> {
+        // Skips the part of the field already typed.
+        let before = self.text[..offset].trim_end_matches(is_ident_char);
+        let mut before = before.strip_suffix('.')?;
+
+        // Collects the names of the path, from the last one.
+        let mut path = Vec::new();
+        loop {
+            let name = &before[before.trim_end_matches(is_ident_char).len()..];
+            if name.is_empty() {
+                return None;
+            }
+            before = &before[..before.len() - name.len()];
+            path.push(name);
+
+            match before.strip_suffix('.') {
+                Some(rest) => before = rest,
+                None => break,
+            }
+        }
+
+        let var = path.pop().unwrap();
+        let mut def = self.struct_def(self.declared_type(var, before.len())?)?;
+        for field in path.into_iter().rev() {
+            match def.fields.get(field)? {
+                ast::Type::Struct(name) => def = self.struct_def(name)?,
+                _ => return None,
+            }
+        }
+        Some(def)
+    }
+
+    // Finds the type of the closest declaration of a variable as an argument before the offset,
+    // like `Position p`.
+    fn declared_type(&self, var: &str, offset: usize) -> Option<&str> {
+        let mut text = &self.text[..offset];
+
+        while let Some(i) = text.rfind(var) {
+            let end = i + var.len();
+            let whole = !self.text[..i].ends_with(is_ident_char) && !self.text[end..].starts_with(is_ident_char);
+            text = &text[..i];
+
+            if whole {
+                let before = text.trim_end();
+                let ty = &before[before.trim_end_matches(is_ident_char).len()..];
+                if before.len() < text.len() && self.struct_def(ty).is_some() {
+                    return Some(ty);
+                }
+            }
+        }
+
+        None
+    }
+
+    // Gets the definition of a component, a resource or a struct.
+    fn struct_def(&self, name: &str) -> Option<&'static StructDef> {
+        match self.ast?.names.get(name)? {
+            Name::Component(def) | Name::Resource(def) | Name::Event(def) | Name::Struct(def) => Some(def),
+            _ => None,
+        }
+    }
+
+    // Returns true if the span is in the document rather than in an included file.
+    fn is_in(&self, span: &Span) -> bool {
+        span.path().as_deref() == Some(&self.path)
+    }
+
+    // Returns the URI of the file the span is in.
+    fn uri_of(&self, span: &Span) -> String {
+        match span.path() {
+            Some(path) if path != self.path => uri_of_path(&path),
+            _ => self.uri.clone(),
+        }
+    }
+}
+
+// The kind of completion items that are fields.
+const FIELD: u32 = 5;
+
+// Returns true if the character may be part of an identifier.
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+// Returns the offsets of the start and of the end of the identifier at the given offset, if there is one.
+fn word_at(text: &str, offset: usize) -> Option<(usize, usize)> {
+    let start = text[..offset].trim_end_matches(is_ident_char).len();
+    let end = offset + text[offset..].len() - text[offset..].trim_start_matches(is_ident_char).len();
+    match text[start..end].starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        true => Some((start, end)),
+        false => None,
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{anyhow, Error, Result};
+use serde_json::{json, Value as Json};
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+mod analysis;
+use analysis::*;
+
+mod protocol;
+use protocol::*;
+
+// The error codes of the protocol.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A language server for C* source files, speaking the Language Server Protocol.
+///
+/// The server publishes the syntax errors of the files opened in the editor, finds
+/// the definitions of the functions, systems, components, resources, events, structs
+/// and enums, shows them on hover, and completes the fields of components, resources,
+/// events and structs after a `.`. Files may be programs or modules.
+#[derive(Default)]
+pub struct LanguageServer {
+    // The documents opened in the editor, by URI.
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+impl LanguageServer {
+    /// Creates a server, with no document opened.
+    pub fn new() -> LanguageServer {
+        LanguageServer::default()
+    }
+
+    /// Answers the messages read from the input until the end of the input or the
+    /// `exit` notification. Returns the exit code, which is 0 if the client asked
+    /// the server to shut down before exiting, as the protocol requires.
+    pub fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> Result<i32> {
+        while let Some(body) = read_message(&mut input)? {
+            let message = match serde_json::from_slice::<Json>(&body) {
+                Ok(message) => message,
+                Err(err) => {
+                    write_message(&mut output, &error(&Json::Null, PARSE_ERROR, format!("Invalid JSON: {}.", err)))?;
+                    continue;
+                }
+            };
+
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+
+            match message.get("id") {
+                // Responses to requests of the server, it doesn't send any.
+                Some(_) if method.is_empty() => (),
+                Some(id) => {
+                    let response = match self.request(method, params) {
+                        Some(Ok(result)) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                        Some(Err(err)) => error(id, INVALID_PARAMS, format!("{:#}", err)),
+                        None => error(id, METHOD_NOT_FOUND, format!("Unknown method {}.", method)),
+                    };
+                    write_message(&mut output, &response)?;
+                }
+                None if method == "exit" => return Ok(if self.shutdown {0} else {1}),
+                None => if let Some(notification) = self.notify(method, params) {
+                    write_message(&mut output, &notification)?;
+                },
+            }
+        }
+
+        Ok(if self.shutdown {0} else {1})
+    }
+
+    // Answers a request, or returns None if the method is unknown.
+    fn request(&mut self, method: &str, params: &Json) -> Option<Result<Json>> {
+        Some(match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // The whole text is sent on each change.
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": {"triggerCharacters": ["."]},
+                },
+                "serverInfo": {"name": "cstar", "version": env!("CARGO_PKG_VERSION")},
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/definition" => self.at_position(params, |document, offset| {
+                document.definition(offset).unwrap_or(Json::Null)
+            }),
+            "textDocument/hover" => self.at_position(params, |document, offset| {
+                document.hover(offset).unwrap_or(Json::Null)
+            }),
+            "textDocument/completion" => self.at_position(params, |document, offset| {
+                Json::Array(document.completions(offset))
+            }),
+            _ => return None,
+        })
+    }
+
+    // Answers a request about a position in a document.
+    fn at_position(&self, params: &Json, answer: impl Fn(&Document, usize) -> Json) -> Result<Json> {
+        let uri = params["textDocument"]["uri"].as_str().ok_or_else(|| anyhow!("Missing document URI."))?;
+        let document = self.documents.get(uri).ok_or_else(|| anyhow!("Document {} is not opened.", uri))?;
+        let offset = offset(document.text(), &params["position"]).ok_or_else(|| anyhow!("Invalid position {}.", params["position"]))?;
+        Ok(answer(document, offset))
+    }
+
+    // Handles a notification, returning the diagnostics to publish if a document changed.
+    fn notify(&mut self, method: &str, params: &Json) -> Option<Json> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+
+        let diagnostics = match method {
+            "textDocument/didOpen" => {
+                let document = Document::new(uri, params["textDocument"]["text"].as_str()?.to_string());
+                let diagnostics = document.diagnostics();
+                self.documents.insert(uri.to_string(), document);
+                diagnostics
+            }
+            "textDocument/didChange" => {
+                let text = params["contentChanges"].as_array()?.last()?["text"].as_str()?;
+                let document = self.documents.get_mut(uri)?;
+                document.update(text.to_string());
+                document.diagnostics()
+            }
+            // Clears the diagnostics of a closed document.
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                Vec::new()
+            }
+            _ => return None,
+        };
+
+        Some(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": uri, "diagnostics": diagnostics},
+        }))
+    }
+}
+
+// Creates the response to a request that failed.
+fn error(id: &Json, code: i64, message: String) -> Json {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
@@ -0,0 +1,117 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use super::*;
+
+// Reads the body of the next message, or returns None at the end of the input.
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut length = None;
+
+    // The headers end with an empty line.
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = Some(value.trim().parse::<usize>().map_err(|_| anyhow!("Invalid Content-Length {}.", value.trim()))?);
+            }
+        }
+    }
+
+    let mut body = vec![0; length.ok_or_else(|| anyhow!("Missing Content-Length header."))?];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+// Writes a message, preceded by its header.
+pub fn write_message(output: &mut impl Write, message: &Json) -> Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+// Converts an offset in bytes in the text to a position, whose character is counted in UTF-16 code units.
+pub fn position(text: &str, offset: usize) -> Json {
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character = before[start..].chars().map(char::len_utf16).sum::<usize>();
+    json!({"line": line, "character": character})
+}
+
+// Converts the start and the end offsets of a part of the text to a range.
+pub fn range(text: &str, (start, end): (usize, usize)) -> Json {
+    json!({"start": position(text, start), "end": position(text, end)})
+}
+
+// Converts a position to an offset in bytes in the text. Positions past the end
+// of a line are moved to its end.
+pub fn offset(text: &str, position: &Json) -> Option<usize> {
+    let line = position["line"].as_u64()? as usize;
+    let character = position["character"].as_u64()? as usize;
+
+    let start = match line {
+        0 => 0,
+        _ => text.match_indices('\n').nth(line - 1)?.0 + 1,
+    };
+
+    let mut units = 0;
+    for (i, c) in text[start..].char_indices() {
+        if units >= character || c == '\n' {
+            return Some(start + i);
+        }
+        units += c.len_utf16();
+    }
+    Some(text.len())
+}
+
+// Gets the path of a file from its URI. Other URIs are used as paths as is.
+pub fn path_of_uri(uri: &str) -> PathBuf {
+    match uri.strip_prefix("file://") {
+        Some(path) => PathBuf::from(percent_decode(path)),
+        None => PathBuf::from(uri),
+    }
+}
+
+// Gets the URI of a file from its path.
+pub fn uri_of_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut uri = String::from("file://");
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(b as char),
+            _ => uri.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    uri
+}
+
+// Replaces the %XX escapes of a URI by the bytes they stand for.
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(decoded) if b == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -1,46 +1,158 @@
-#![allow(unused)]
-
-mod ast;
-mod parser;
-mod eval;
-mod sources;
-mod utils;
-
+use std::fs;
+use std::io::{self, Read, Write};
 use std::path::Path;
+use std::process;
 
-use anyhow::Result;
-use clap::{App, Arg};
+use anyhow::{anyhow, Result};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use cstar::{Interpreter, LanguageServer, Repl};
 
-fn main() -> Result<()> {
+fn main() {
+    match run() {
+        Ok(code) => {
+            io::stdout().flush().ok();
+            process::exit(code);
+        }
+        // Prints the error in a human readable way, without backtrace.
+        Err(err) => {
+            io::stdout().flush().ok();
+            eprintln!("error: {:#}", err);
+            process::exit(1);
+        }
+    }
+}
+
+// Runs the interpreter, returning the exit code of the program.
+fn run() -> Result<i32> {
     // Parses the CLI arguments.
     let args = App::new("C* interpreter")
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about("Interpreter for the C* programming language.")
+        // Only the exact names of the subcommands are subcommands, so that a source named
+        // like one is still run. The other names are external subcommands, checked below.
+        .setting(AppSettings::AllowExternalSubcommands)
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .subcommand(SubCommand::with_name("repl")
+            .about("Starts an interactive session, running definitions and statements as they are typed."))
+        .subcommand(SubCommand::with_name("fmt")
+            .about("Formats source files in place, to the canonical layout.")
+            .arg(Arg::with_name("files")
+                .value_name("FILE")
+                .help("The paths to the source files to format, - formatting the standard input to the standard output.")
+                .multiple(true)
+                .required(true))
+            .arg(Arg::with_name("check")
+                .long("check")
+                .help("Lists the files that are not formatted instead of formatting them, failing if there are any.")))
+        .subcommand(SubCommand::with_name("lsp")
+            .about("Starts a language server for editors, speaking the Language Server Protocol over stdio."))
         .arg(Arg::with_name("source")
             .index(1)
             .value_name("SOURCE")
-            .help("The path to the source file to be interpreted.")
-            .required(true))
+            .help("The path to the source file to be interpreted."))
         .arg(Arg::with_name("ast")
             .long("ast")
             .help("Prints the AST of the source file and quits before evaluating it."))
+        .arg(Arg::with_name("vm")
+            .long("vm")
+            .help("Runs the program with the bytecode VM instead of the tree-walking evaluator."))
+        .arg(Arg::with_name("load")
+            .long("load")
+            .value_name("FILE")
+            .help("Loads the world saved in the given file before running the program."))
+        .arg(Arg::with_name("save")
+            .long("save")
+            .value_name("FILE")
+            .help("Saves the world to the given file once the program has run."))
         .get_matches();
 
+    // Starts the REPL if requested.
+    if args.subcommand_matches("repl").is_some() {
+        println!("C* {}, type :help for help.", env!("CARGO_PKG_VERSION"));
+        return Repl::new().run(io::stdin().lock());
+    }
+
+    // Formats files if requested.
+    if let Some(args) = args.subcommand_matches("fmt") {
+        return format(args);
+    }
+
+    // Starts the language server if requested.
+    if args.subcommand_matches("lsp").is_some() {
+        return LanguageServer::new().run(io::stdin().lock(), io::stdout().lock());
+    }
+
+    // Any argument after the source is an external subcommand.
+    if let (name, Some(extra)) = args.subcommand() {
+        let extra = extra.values_of("").into_iter().flatten();
+        return Err(anyhow!("Unexpected argument {}.", std::iter::once(name).chain(extra).collect::<Vec<_>>().join(" ")));
+    }
+
     // Gets the source file's path.
-    let path = Path::new(args.value_of("source").unwrap());
+    let path = Path::new(args.value_of("source").ok_or_else(|| anyhow!("No source file given, see --help."))?);
 
-    // Parses the AST.
-    let ast = parser::parse_program(path)?;
+    // Parses the program.
+    let mut interpreter = Interpreter::from_path(path)?;
 
     // Prints the ast and exits if requested.
     if args.is_present("ast") {
-        dbg!(&ast);
-        return Ok(());
+        dbg!(interpreter.ast());
+        return Ok(0);
     }
 
-    // Evaluates the AST.
-    eval::eval(&ast)?;
+    if args.is_present("vm") {
+        interpreter.use_vm(true);
+    }
+
+    // Checks the program, printing the warnings even if there are errors.
+    let checked = interpreter.check();
+    for warning in interpreter.warnings() {
+        eprintln!("warning: {:#}\n", warning);
+    }
+    checked?;
+
+    // Restores the saved world, before the Init systems run.
+    if let Some(path) = args.value_of("load") {
+        interpreter.load_world(path)?;
+    }
+
+    // Evaluates the program, exiting with the code it requested.
+    let code = interpreter.run()?;
+
+    if let Some(path) = args.value_of("save") {
+        interpreter.save_world(path)?;
+    }
+    Ok(code)
+}
+
+// Formats the files given to the fmt command, returning the exit code.
+fn format(args: &ArgMatches) -> Result<i32> {
+    let check = args.is_present("check");
+    let mut unformatted = false;
+
+    for path in args.values_of("files").unwrap() {
+        let code = match path {
+            "-" => {
+                let mut code = String::new();
+                io::stdin().read_to_string(&mut code)?;
+                code
+            }
+            _ => fs::read_to_string(path).map_err(|err| anyhow!("Could not read {}: {}.", path, err))?,
+        };
+
+        let formatted = cstar::format(Path::new(path), &code)?;
+        if check {
+            if formatted != code {
+                println!("{} is not formatted.", path);
+                unformatted = true;
+            }
+        } else if path == "-" {
+            print!("{}", formatted);
+        } else if formatted != code {
+            fs::write(path, formatted).map_err(|err| anyhow!("Could not write {}: {}.", path, err))?;
+        }
+    }
 
-    Ok(())
+    Ok(if unformatted {1} else {0})
 }
@@ -1,54 +1,55 @@
 use lazy_static::lazy_static;
-use pest::prec_climber::{Assoc, Operator, PrecClimber};
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 
 use std::str::FromStr;
 
 use super::*;
 
 // Parses an expression.
-pub fn parse_expr(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    let pair = pairs.next().unwrap();
+pub fn parse_expr(pair: Pair<'static, Rule>) -> ast::Expr {
+    let pair = pair.into_inner().next().unwrap();
 
     match pair.as_rule() {
-        Rule::binexpr => parse_binexpr(pair.into_inner()),
-        Rule::ternary => parse_ternary(pair.into_inner()),
-        Rule::term => parse_term(pair.into_inner()),
+        Rule::binexpr => parse_binexpr(pair),
+        Rule::ternary => parse_ternary(pair),
+        Rule::term => parse_term(pair),
         _ => unreachable!(),
     }
 }
 
 lazy_static! {
-    // The precedence climber for the expression grammar.
-    static ref PREC_CLIMBER: PrecClimber<Rule> = {
+    // The pratt parser for the expression grammar, from lowest to highest precedence.
+    static ref PRATT_PARSER: PrattParser<Rule> = {
         use Rule::*;
         use Assoc::*;
 
-        PrecClimber::new(vec![
-            Operator::new(or, Left),
-            Operator::new(and, Left),
-            Operator::new(bitor, Left),
-            Operator::new(xor, Left),
-            Operator::new(bitand, Left), 
-            Operator::new(eq, Left) | Operator::new(neq, Left),
-            Operator::new(leq, Left) | Operator::new(geq, Left) | Operator::new(lt, Left) | Operator::new(gt, Left),
-            Operator::new(shl, Left) | Operator::new(shr, Left),
-            Operator::new(add, Left) | Operator::new(sub, Left),
-            Operator::new(mul, Left) | Operator::new(div, Left) | Operator::new(mod_, Left),
-        ])
+        PrattParser::new()
+            .op(Op::infix(or, Left))
+            .op(Op::infix(and, Left))
+            .op(Op::infix(bitor, Left))
+            .op(Op::infix(xor, Left))
+            .op(Op::infix(bitand, Left))
+            .op(Op::infix(eq, Left) | Op::infix(neq, Left))
+            .op(Op::infix(leq, Left) | Op::infix(geq, Left) | Op::infix(lt, Left) | Op::infix(gt, Left))
+            .op(Op::infix(shl, Left) | Op::infix(shr, Left))
+            .op(Op::infix(add, Left) | Op::infix(sub, Left))
+            .op(Op::infix(mul, Left) | Op::infix(div, Left) | Op::infix(mod_, Left))
     };
 }
 
 // Parses a binary expression.
-pub fn parse_binexpr(pairs: Pairs<'static, Rule>) -> ast::Expr {
-    PREC_CLIMBER.climb(
-        pairs,
-        |pair| parse_term(pair.into_inner()),
-        |left, op, right| ast::Expr::BinExpr(Box::new(ast::BinExpr {
-            left,
-            op: parse_binop(op),
-            right,
-        })),
-    )
+pub fn parse_binexpr(pair: Pair<'static, Rule>) -> ast::Expr {
+    PRATT_PARSER
+        .map_primary(parse_term)
+        .map_infix(|left, op, right| ast::Expr {
+            span: left.span.to(right.span),
+            kind: ast::ExprKind::BinExpr(Box::new(ast::BinExpr {
+                left,
+                op: parse_binop(op),
+                right,
+            })),
+        })
+        .parse(pair.into_inner())
 }
 
 // Parses a binary operator.
@@ -77,52 +78,108 @@ pub fn parse_binop(pair: Pair<'_, Rule>) -> ast::BinOp {
 }
 
 // Parses a ternary expression.
-pub fn parse_ternary(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    ast::Expr::Ternary(Box::new(ast::Ternary {
-        cond: parse_expr(pairs.next().unwrap().into_inner()),
-        branch1: parse_expr(pairs.next().unwrap().into_inner()),
-        branch2: parse_expr(pairs.next().unwrap().into_inner()),
-    }))
+pub fn parse_ternary(pair: Pair<'static, Rule>) -> ast::Expr {
+    let span = pair.as_span().into();
+    let mut pairs = pair.into_inner();
+
+    ast::Expr {
+        kind: ast::ExprKind::Ternary(Box::new(ast::Ternary {
+            cond: parse_expr(pairs.next().unwrap()),
+            branch1: parse_expr(pairs.next().unwrap()),
+            branch2: parse_expr(pairs.next().unwrap()),
+        })),
+        span,
+    }
 }
 
 // Parses a term.
-pub fn parse_term(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    let pair = pairs.next().unwrap();
+pub fn parse_term(pair: Pair<'static, Rule>) -> ast::Expr {
+    let pair = pair.into_inner().next().unwrap();
 
     match pair.as_rule() {
-        Rule::value => parse_value(pair.into_inner()),
-        Rule::unexpr => parse_unexpr(pair.into_inner()),
-        Rule::expr => parse_expr(pair.into_inner()),
+        Rule::value => parse_value(pair),
+        Rule::unexpr => parse_unexpr(pair),
+        Rule::expr => parse_expr(pair),
         _ => unreachable!(),        
     }
 }
 
 // Parses a value.
-pub fn parse_value(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    let pair = pairs.next().unwrap();
+pub fn parse_value(pair: Pair<'static, Rule>) -> ast::Expr {
+    let span = pair.as_span().into();
+    let pair = pair.into_inner().next().unwrap();
+
+    let kind = match pair.as_rule() {
+        Rule::assign => parse_assign(pair),
+        Rule::pre_incr => parse_incr(pair, true),
+        Rule::post_incr => parse_incr(pair, false),
+        Rule::atom => ast::ExprKind::Atom(parse_atom(pair)),
+        Rule::call => ast::ExprKind::Call(parse_call(pair)),
+        Rule::list_init => parse_list_init(pair),
+        Rule::struct_init => parse_struct_init(pair),
+        Rule::enum_init => parse_enum_init(pair),
+        Rule::lvalue => ast::ExprKind::LValue(parse_lvalue(pair)),
+        Rule::lambda => ast::ExprKind::Function(Box::new(parse_function_def(pair.into_inner()))),
+        _ => unreachable!(),
+    };
+
+    ast::Expr {kind, span}
+}
 
+// Parses an assignement.
+pub fn parse_assign(pair: Pair<'static, Rule>) -> ast::ExprKind {
+    let mut pairs = pair.into_inner();
+
+    ast::ExprKind::Assign(Box::new(ast::Assign {
+        lvalue: parse_lvalue(pairs.next().unwrap()),
+        op: parse_assign_op(pairs.next().unwrap()),
+        expr: parse_expr(pairs.next().unwrap()),
+    }))
+}
+
+// Parses an assignement operator, returning the binary operator of compound assignments.
+pub fn parse_assign_op(pair: Pair<'_, Rule>) -> Option<ast::BinOp> {
     match pair.as_rule() {
-        Rule::assign => parse_assign(pair.into_inner()),
-        Rule::atom => ast::Expr::Atom(parse_atom(pair.into_inner())),
-        Rule::call => parse_call(pair.into_inner()),
-        Rule::list_init => parse_list_init(pair.into_inner()),
-        Rule::struct_init => parse_struct_init(pair.into_inner()),
-        Rule::lvalue => ast::Expr::LValue(parse_lvalue(pair.into_inner())),
+        Rule::set => None,
+        Rule::add_set => Some(ast::BinOp::Add),
+        Rule::sub_set => Some(ast::BinOp::Sub),
+        Rule::mul_set => Some(ast::BinOp::Mul),
+        Rule::div_set => Some(ast::BinOp::Div),
+        Rule::mod_set => Some(ast::BinOp::Mod),
+        Rule::bitand_set => Some(ast::BinOp::BitAnd),
+        Rule::bitor_set => Some(ast::BinOp::BitOr),
+        Rule::xor_set => Some(ast::BinOp::Xor),
+        Rule::shl_set => Some(ast::BinOp::Shl),
+        Rule::shr_set => Some(ast::BinOp::Shr),
         _ => unreachable!(),
     }
 }
 
-// Parses an assignement.
-pub fn parse_assign(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    ast::Expr::Assign(Box::new(ast::Assign {
-        lvalue: parse_lvalue(pairs.next().unwrap().into_inner()),
-        expr: parse_expr(pairs.next().unwrap().into_inner()),
-    }))
+// Parses an increment or a decrement.
+pub fn parse_incr(pair: Pair<'static, Rule>, prefix: bool) -> ast::ExprKind {
+    let mut pairs = pair.into_inner();
+    let (op, lvalue) = match prefix {
+        true => (pairs.next().unwrap(), pairs.next().unwrap()),
+        false => {
+            let lvalue = pairs.next().unwrap();
+            (pairs.next().unwrap(), lvalue)
+        }
+    };
+
+    ast::ExprKind::Incr(ast::Incr {
+        lvalue: parse_lvalue(lvalue),
+        op: match op.as_rule() {
+            Rule::inc => ast::IncrOp::Inc,
+            Rule::dec => ast::IncrOp::Dec,
+            _ => unreachable!(),
+        },
+        prefix,
+    })
 }
 
 // Parses an atom.
-pub fn parse_atom(mut pairs: Pairs<'static, Rule>) -> ast::Atom {
-    let pair = pairs.next().unwrap();
+pub fn parse_atom(pair: Pair<'static, Rule>) -> ast::Atom {
+    let pair = pair.into_inner().next().unwrap();
 
     match pair.as_rule() {
         Rule::void => ast::Atom::Void,
@@ -181,54 +238,77 @@ pub fn parse_string(s: &str) -> String {
 }
 
 // Parses a call.
-pub fn parse_call(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    ast::Expr::Call(ast::Call {
+pub fn parse_call(pair: Pair<'static, Rule>) -> ast::Call {
+    let span = pair.as_span().into();
+    let mut pairs = pair.into_inner();
+
+    ast::Call {
         name: pairs.next().unwrap().as_str(),
-        args: pairs.map(|pair| parse_expr(pair.into_inner())).collect(),
-    })
+        args: pairs.map(parse_expr).collect(),
+        span,
+    }
 }
 
 // Parses a list initialization.
-pub fn parse_list_init(pairs: Pairs<'static, Rule>) -> ast::Expr {
-    ast::Expr::ListInit(ast::ListInit {
-        exprs: pairs.map(|pair| parse_expr(pair.into_inner())).collect(),
+pub fn parse_list_init(pair: Pair<'static, Rule>) -> ast::ExprKind {
+    ast::ExprKind::ListInit(ast::ListInit {
+        exprs: pair.into_inner().map(parse_expr).collect(),
     })
 }
 
 // Parses a struct initialization.
-pub fn parse_struct_init(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
+pub fn parse_struct_init(pair: Pair<'static, Rule>) -> ast::ExprKind {
+    let mut pairs = pair.into_inner();
     let name = pairs.next().unwrap().as_str();
     let mut fields = Vec::new();
 
     while let Some(pair) = pairs.next() {
         fields.push((
             pair.as_str(),
-            parse_expr(pairs.next().unwrap().into_inner()),
+            parse_expr(pairs.next().unwrap()),
         ));
     }
 
-    ast::Expr::StructInit(ast::StructInit {name, fields})
+    ast::ExprKind::StructInit(ast::StructInit {name, fields})
+}
+
+// Parses an enum value.
+pub fn parse_enum_init(pair: Pair<'static, Rule>) -> ast::ExprKind {
+    let mut pairs = pair.into_inner();
+
+    ast::ExprKind::EnumInit(ast::EnumInit {
+        name: pairs.next().unwrap().as_str(),
+        variant: pairs.next().unwrap().as_str(),
+        args: pairs.map(parse_expr).collect(),
+    })
 }
 
 // Parses an index.
-pub fn parse_index(pairs: Pairs<'static, Rule>) -> ast::Index {
+pub fn parse_index(pair: Pair<'static, Rule>) -> ast::Index {
     ast::Index {
-        exprs: pairs.map(|pair| parse_expr(pair.into_inner())).collect(),
+        exprs: pair.into_inner().map(parse_expr).collect(),
     }
 }
 
 // Parses a left-value.
-pub fn parse_lvalue(mut pairs: Pairs<'static, Rule>) -> ast::LValue {
+pub fn parse_lvalue(pair: Pair<'static, Rule>) -> ast::LValue {
+    // The span ends with the last part that isn't empty, rather than after the whitespace
+    // matched before an empty index.
+    let last = pair.clone().into_inner().filter(|pair| !pair.as_str().is_empty()).last().unwrap();
+    let span = ast::Span::from(pair.as_span()).to(last.as_span().into());
+    let mut pairs = pair.into_inner();
+
     let mut res = ast::LValue {
         name: pairs.next().unwrap().as_str(),
-        first_index: parse_index(pairs.next().unwrap().into_inner()),
+        first_index: parse_index(pairs.next().unwrap()),
         path: Vec::new(),
+        span,
     };
 
     while let Some(pair) = pairs.next() {
         res.path.push((
             pair.as_str(), 
-            parse_index(pairs.next().unwrap().into_inner())
+            parse_index(pairs.next().unwrap())
         ));
     }
 
@@ -236,20 +316,26 @@ pub fn parse_lvalue(mut pairs: Pairs<'static, Rule>) -> ast::LValue {
 }
 
 // Parses a unary expression.
-pub fn parse_unexpr(mut pairs: Pairs<'static, Rule>) -> ast::Expr {
-    ast::Expr::UnExpr(Box::new(ast::UnExpr {
-        op: parse_unop(pairs.next().unwrap().into_inner()),
-        expr: parse_term(pairs.next().unwrap().into_inner()),
-    }))
+pub fn parse_unexpr(pair: Pair<'static, Rule>) -> ast::Expr {
+    let span = pair.as_span().into();
+    let mut pairs = pair.into_inner();
+
+    ast::Expr {
+        kind: ast::ExprKind::UnExpr(Box::new(ast::UnExpr {
+            op: parse_unop(pairs.next().unwrap()),
+            expr: parse_term(pairs.next().unwrap()),
+        })),
+        span,
+    }
 }
 
 // Parses a unary operator.
-pub fn parse_unop(mut pairs: Pairs<'static, Rule>) -> ast::UnOp {
-    match pairs.next().unwrap().as_rule() {
+pub fn parse_unop(pair: Pair<'static, Rule>) -> ast::UnOp {
+    match pair.into_inner().next().unwrap().as_rule() {
         Rule::pos => ast::UnOp::Pos,
         Rule::neg => ast::UnOp::Neg,
         Rule::not => ast::UnOp::Not,
         Rule::bitnot => ast::UnOp::BitNot,
         _ => unreachable!(),
     }
-}
\ No newline at end of file
+}
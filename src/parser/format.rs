@@ -0,0 +1,800 @@
+// Formats source code to a canonical layout, working on the parse tree rather than on the
+// AST so that nothing but the layout changes. Comments other than doc comments are not part of
+// the parse tree: they are found back in the source code between the tokens that are written,
+// and kept where they were, either on their own line or at the end of the line of the code they follow.
+
+use super::*;
+
+// The width past which calls, lists and struct initializations are split over several lines.
+const WIDTH: usize = 100;
+
+// The indentation of a level of blocks.
+const INDENT: &str = "    ";
+
+/// Formats the source code of a C* program or module to the canonical layout,
+/// keeping its comments. The path is only used to locate syntax errors, and
+/// includes are not followed.
+///
+/// ```
+/// use std::path::Path;
+///
+/// let code = "System main(){println( \"hi\" );} /* Entry point. */\nInit [main];Run [];";
+/// let formatted = cstar::format(Path::new("main.cstar"), code)?;
+/// assert_eq!(formatted, "System main() {\n    println(\"hi\");\n} /* Entry point. */\n\nInit [main];\nRun [];\n");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn format(path: &Path, code: &str) -> Result<String> {
+    let mut src = Sources::default();
+    let file = src.add_str(path, code.to_string());
+    let pairs = parse_file(Rule::document, file)?;
+
+    let mut formatter = Formatter {
+        src: file,
+        pos: 0,
+        out: String::new(),
+        indent: 0,
+        inline: false,
+    };
+    formatter.document(pairs);
+    Ok(formatter.out)
+}
+
+// A comment found between two tokens.
+struct Comment {
+    text: &'static str,
+    // The column the comment started at.
+    column: usize,
+    // True if the comment is the first thing on its line.
+    own_line: bool,
+    // True if there is an empty line right before the comment.
+    blank_before: bool,
+}
+
+// How the items of a group are separated.
+#[derive(Clone, Copy, PartialEq)]
+enum Separator {
+    // Arguments of calls and items of lists, like `f(a, b)`.
+    Comma,
+    // Fields of struct initializations, like `P { x: 1; y: 2; }`.
+    Semicolon,
+}
+
+struct Formatter {
+    src: &'static str,
+    // The offset in the source of the end of the last token written.
+    pos: usize,
+    out: String,
+    indent: usize,
+    // True while trying to fit a group on a single line.
+    inline: bool,
+}
+
+// Writing and comments.
+impl Formatter {
+    fn write(&mut self, s: &str) {
+        self.out.push_str(s);
+    }
+
+    // Writes a token of the source, after the comments before it.
+    fn token(&mut self, pair: &Pair<'static, Rule>) {
+        self.inline_comments(pair.as_span().start());
+        self.write(pair.as_str());
+        self.pos = pair.as_span().end();
+    }
+
+    // Returns the number of characters of the current line.
+    fn column(&self) -> usize {
+        self.column_at(self.out.len())
+    }
+
+    // Returns the number of characters of the line of the output ending at the given offset.
+    fn column_at(&self, end: usize) -> usize {
+        self.out[self.out[..end].rfind('\n').map_or(0, |i| i + 1)..end].chars().count()
+    }
+
+    // Returns true if nothing but the indentation was written on the current line.
+    fn at_line_start(&self) -> bool {
+        self.out[self.out.rfind('\n').map_or(0, |i| i + 1)..].trim().is_empty()
+    }
+
+    // Removes the spaces at the end of the output.
+    fn trim(&mut self) {
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+    }
+
+    // Starts a new line at the current indentation, possibly after an empty line.
+    fn newline(&mut self, blank: bool) {
+        self.trim();
+        if !self.out.is_empty() {
+            self.out.push('\n');
+            if blank {
+                self.out.push('\n');
+            }
+        }
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    // Collects the comments of the source between the last token written and the given offset.
+    // Also returns true if there is an empty line right before that offset.
+    fn gap(&mut self, until: usize) -> (Vec<Comment>, bool) {
+        let mut comments = Vec::new();
+        let mut newlines = 0;
+        let mut i = self.pos;
+
+        while i < until {
+            let rest = &self.src[i..until];
+            let len = if rest.starts_with("/*") {
+                Some(rest.find("*/").map_or(rest.len(), |end| end + 2))
+            } else if rest.starts_with("//") {
+                Some(rest.find('\n').unwrap_or(rest.len()))
+            } else {
+                None
+            };
+
+            if let Some(len) = len {
+                let line = self.src[..i].rfind('\n').map_or(0, |nl| nl + 1);
+                comments.push(Comment {
+                    text: &rest[..len],
+                    column: self.src[line..i].chars().count(),
+                    own_line: self.src[line..i].trim().is_empty(),
+                    blank_before: newlines > 1,
+                });
+                newlines = 0;
+                i += len;
+            } else {
+                let c = rest.chars().next().unwrap();
+                match c {
+                    '\n' => newlines += 1,
+                    ' ' | '\t' => (),
+                    _ => newlines = 0,
+                }
+                i += c.len_utf8();
+            }
+        }
+
+        self.pos = self.pos.max(until);
+        (comments, newlines > 1)
+    }
+
+    // Writes a comment, moving the lines after the first one along with it.
+    fn comment(&mut self, comment: &Comment) {
+        let column = self.column();
+        let mut lines = comment.text.split('\n');
+        self.write(lines.next().unwrap());
+        for line in lines {
+            let indent = line.len() - line.trim_start().len();
+            let line = &line[indent.min(comment.column)..];
+            self.out.push('\n');
+            if !line.is_empty() {
+                self.write(&" ".repeat(column));
+                self.write(line);
+            }
+        }
+    }
+
+    // Writes the comments before the given offset in the middle of a line. The line
+    // goes on after them, unless they are line comments.
+    fn inline_comments(&mut self, until: usize) {
+        for comment in self.gap(until).0 {
+            if !self.at_line_start() && !self.out.ends_with([' ', '(', '[']) {
+                self.write(" ");
+            }
+            self.comment(&comment);
+            match comment.text.starts_with("//") {
+                true => self.newline(false),
+                false => self.write(" "),
+            }
+        }
+    }
+
+    // Writes the comments before the given offset, each on its own line unless it follows
+    // code on the line it was on. An empty line is put before the first comment if `blank`
+    // says so, or if there is one in the source if it is None. Returns whether there should
+    // be an empty line before what comes next.
+    fn comments(&mut self, until: usize, mut blank: Option<bool>) -> bool {
+        let (comments, blank_after) = self.gap(until);
+
+        for comment in comments {
+            if !comment.own_line && !self.at_line_start() {
+                self.write(" ");
+            } else {
+                self.newline(blank.unwrap_or(comment.blank_before));
+                blank = None;
+            }
+            self.comment(&comment);
+        }
+
+        blank.unwrap_or(blank_after)
+    }
+
+    // Starts the line of an item starting at the given offset, after the comments before it.
+    fn line(&mut self, start: usize, blank: Option<bool>) {
+        let blank = self.comments(start, blank);
+        self.newline(blank);
+    }
+
+    // Starts a block of items, opened by the given delimiter.
+    fn open(&mut self, delim: &str) {
+        self.write(delim);
+        self.indent += 1;
+    }
+
+    // Ends a block of items with the delimiter at the given offset, after the last comments in it.
+    fn close(&mut self, at: usize, delim: &str) {
+        self.comments(at, Some(false));
+        self.indent -= 1;
+        if !self.out.ends_with(['{', '(', '[']) {
+            self.newline(false);
+        }
+        self.write(delim);
+        self.pos = at + delim.len();
+    }
+
+    // Writes the items of a block, each on its own line, followed by its closing delimiter.
+    fn items(&mut self, items: Vec<Pair<'static, Rule>>, end: usize, delim: &str, mut item: impl FnMut(&mut Self, Pair<'static, Rule>)) {
+        for (i, pair) in items.into_iter().enumerate() {
+            self.line(pair.as_span().start(), if i == 0 {Some(false)} else {None});
+            item(self, pair);
+        }
+        self.close(end - delim.len(), delim);
+    }
+}
+
+// Files and elements.
+impl Formatter {
+    fn document(&mut self, pairs: Pairs<'static, Rule>) {
+        let mut previous = None;
+
+        for pair in pairs {
+            let rule = pair.as_rule();
+            let blank = match (previous, rule) {
+                (_, Rule::EOI) => None,
+                (None, _) | (Some(Rule::include), Rule::include) => Some(false),
+                (Some(Rule::init | Rule::run), Rule::run | Rule::shutdown) => Some(false),
+                _ => Some(true),
+            };
+
+            match rule {
+                Rule::EOI => {
+                    self.comments(self.src.len(), blank);
+                    let len = self.out.trim_end().len();
+                    self.out.truncate(len);
+                    if !self.out.is_empty() {
+                        self.out.push('\n');
+                    }
+                }
+                Rule::include => {
+                    self.line(pair.as_span().start(), blank);
+                    self.write("include ");
+                    self.token(&pair.into_inner().next().unwrap());
+                    self.write(";");
+                }
+                Rule::element => {
+                    self.line(pair.as_span().start(), blank);
+                    let mut pairs = pair.into_inner().peekable();
+                    if let Some(doc) = pairs.next_if(|pair| pair.as_rule() == Rule::doc) {
+                        self.doc(&doc);
+                        self.line(pairs.peek().unwrap().as_span().start(), Some(false));
+                    }
+                    self.element(pairs.next().unwrap());
+                }
+                Rule::init | Rule::run | Rule::shutdown => {
+                    self.line(pair.as_span().start(), blank);
+                    self.write(match rule {
+                        Rule::init => "Init [",
+                        Rule::run => "Run [",
+                        _ => "Shutdown [",
+                    });
+                    self.separated(pair.into_inner(), ", ", |f, pair| f.token(&pair));
+                    self.write("];");
+                }
+                _ => unreachable!(),
+            }
+
+            previous = Some(rule);
+        }
+    }
+
+    // Writes the doc comment of an element, on the lines before it.
+    fn doc(&mut self, pair: &Pair<'static, Rule>) {
+        let start = pair.as_span().start();
+        let line = self.src[..start].rfind('\n').map_or(0, |nl| nl + 1);
+        self.comment(&Comment {
+            text: pair.as_str(),
+            column: self.src[line..start].chars().count(),
+            own_line: true,
+            blank_before: false,
+        });
+        self.pos = pair.as_span().end();
+    }
+
+    fn element(&mut self, pair: Pair<'static, Rule>) {
+        let rule = pair.as_rule();
+        let end = pair.as_span().end();
+        let mut pairs = pair.into_inner();
+
+        match rule {
+            Rule::component | Rule::resource | Rule::event | Rule::struct_ => {
+                self.write(match rule {
+                    Rule::component => "Component ",
+                    Rule::resource => "Resource ",
+                    Rule::event => "Event ",
+                    _ => "struct ",
+                });
+                self.token(&pairs.next().unwrap());
+                self.write(" ");
+                self.struct_def(pairs.next().unwrap());
+            }
+            Rule::enum_ => {
+                self.write("enum ");
+                self.token(&pairs.next().unwrap());
+                self.open(" {");
+                self.items(pairs.collect(), end, "}", |f, variant| {
+                    let mut pairs = variant.into_inner();
+                    f.token(&pairs.next().unwrap());
+                    f.optional_list(pairs, |f, pair| f.token(&pair));
+                    f.write(",");
+                });
+            }
+            Rule::system => {
+                self.write("System ");
+                self.token(&pairs.next().unwrap());
+                self.write("(");
+                let mut next = pairs.next().unwrap();
+                if next.as_rule() == Rule::filter {
+                    self.filter(next);
+                    next = pairs.next().unwrap();
+                }
+                self.write(") ");
+                self.block(next);
+            }
+            Rule::function => {
+                self.write("function ");
+                let pairs = pairs.collect::<Vec<_>>();
+                let (body, mut signature) = pairs.split_last().unwrap();
+                if signature[0].as_rule() == Rule::type_ {
+                    self.token(&signature[0]);
+                    self.write(" ");
+                    signature = &signature[1..];
+                }
+                self.token(&signature[0]);
+                self.params(&signature[1..]);
+                self.block(body.clone());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Writes the parameters of a function, named or not, up to its body.
+    fn params(&mut self, params: &[Pair<'static, Rule>]) {
+        self.write("(");
+        self.separated(params.iter().cloned(), ", ", |f, param| {
+            f.separated(param.into_inner(), " ", |f, pair| f.token(&pair));
+        });
+        self.write(") ");
+    }
+
+    fn struct_def(&mut self, pair: Pair<'static, Rule>) {
+        let end = pair.as_span().end();
+        let pairs = pair.into_inner().collect::<Vec<_>>();
+
+        self.open("{");
+        for (i, field) in pairs.chunks(2).enumerate() {
+            self.line(field[0].as_span().start(), if i == 0 {Some(false)} else {None});
+            self.token(&field[0]);
+            self.write(" ");
+            self.token(&field[1]);
+            self.write(";");
+        }
+        self.close(end - 1, "}");
+    }
+
+    fn filter(&mut self, pair: Pair<'static, Rule>) {
+        self.separated(pair.into_inner(), "; ", |f, pair| match pair.as_rule() {
+            Rule::entity_filter => f.entity_filter(pair),
+            _ => f.filter_arg(pair),
+        });
+    }
+
+    fn entity_filter(&mut self, pair: Pair<'static, Rule>) {
+        let mut pairs = pair.into_inner();
+        self.write("Entity ");
+        self.token(&pairs.next().unwrap());
+        for pair in pairs {
+            self.write(", ");
+            self.filter_arg(pair);
+        }
+    }
+
+    fn filter_arg(&mut self, pair: Pair<'static, Rule>) {
+        match pair.as_rule() {
+            Rule::without => self.write("!"),
+            Rule::optional => self.write("?"),
+            Rule::added | Rule::changed => {
+                self.write(if pair.as_rule() == Rule::added {"Added<"} else {"Changed<"});
+                self.token(&pair.into_inner().next().unwrap());
+                self.write(">");
+                return;
+            }
+            Rule::events => {
+                let mut pairs = pair.into_inner();
+                self.write("Events<");
+                self.token(&pairs.next().unwrap());
+                self.write("> ");
+                self.token(&pairs.next().unwrap());
+                return;
+            }
+            _ => (),
+        }
+        self.separated(pair.into_inner(), " ", |f, pair| f.token(&pair));
+    }
+}
+
+// Statements.
+impl Formatter {
+    fn block(&mut self, pair: Pair<'static, Rule>) {
+        let end = pair.as_span().end();
+        // Empty statements are left out.
+        let statements = pair.into_inner().filter(|stmt| stmt.clone().into_inner().next().is_some()).collect();
+        self.open("{");
+        self.items(statements, end, "}", Self::statement);
+    }
+
+    fn statement(&mut self, pair: Pair<'static, Rule>) {
+        let pair = pair.into_inner().next().unwrap();
+
+        let rule = pair.as_rule();
+        let end = pair.as_span().end();
+        let mut pairs = pair.clone().into_inner();
+
+        match rule {
+            Rule::if_ => {
+                self.write("if (");
+                self.expr(pairs.next().unwrap());
+                self.write(") ");
+                self.block(pairs.next().unwrap());
+                if let Some(branch) = pairs.next() {
+                    self.write(" else ");
+                    self.block(branch);
+                }
+            }
+            Rule::for_ => {
+                self.write("for (");
+                self.separated(pairs.by_ref().take(3), "; ", |f, pair| match pair.as_rule() {
+                    Rule::decl => f.decl(pair),
+                    _ => f.expr(pair),
+                });
+                self.write(") ");
+                self.block(pairs.next().unwrap());
+            }
+            Rule::while_ => {
+                self.write("while (");
+                self.expr(pairs.next().unwrap());
+                self.write(") ");
+                self.block(pairs.next().unwrap());
+            }
+            Rule::query => {
+                self.write("query(");
+                self.entity_filter(pairs.next().unwrap());
+                self.write(") ");
+                self.block(pairs.next().unwrap());
+            }
+            Rule::switch => {
+                self.write("switch (");
+                self.expr(pairs.next().unwrap());
+                self.open(") {");
+                self.items(pairs.collect(), end, "}", |f, case| {
+                    let rule = case.as_rule();
+                    let mut pairs = case.into_inner();
+                    match rule {
+                        Rule::case => {
+                            f.write("case ");
+                            let pattern = pairs.next().unwrap();
+                            match pattern.as_rule() {
+                                Rule::pattern => f.path(pattern),
+                                _ => f.token(&pattern),
+                            }
+                            f.write(": ");
+                        }
+                        _ => f.write("default: "),
+                    }
+                    f.block(pairs.next().unwrap());
+                });
+            }
+            Rule::block => self.block(pair),
+            Rule::break_ | Rule::continue_ => {
+                self.token(&pair);
+                self.write(";");
+            }
+            Rule::return_ => {
+                self.write("return");
+                if let Some(expr) = pairs.next() {
+                    self.write(" ");
+                    self.expr(expr);
+                }
+                self.write(";");
+            }
+            Rule::decl => {
+                self.decl(pair);
+                self.write(";");
+            }
+            Rule::expr => {
+                self.expr(pair);
+                self.write(";");
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn decl(&mut self, pair: Pair<'static, Rule>) {
+        let mut pairs = pair.into_inner();
+        self.write("let ");
+        self.token(&pairs.next().unwrap());
+        if let Some(init) = pairs.next() {
+            self.write(" = ");
+            self.expr(init);
+        }
+    }
+
+    // Writes a path to a variant of an enum like `Shape::Circle`, followed by the values it holds if any.
+    // Used both by switch patterns and by enum initializations.
+    fn path(&mut self, pair: Pair<'static, Rule>) {
+        let rule = pair.as_rule();
+        let mut pairs = pair.into_inner();
+        self.token(&pairs.next().unwrap());
+        self.write("::");
+        self.token(&pairs.next().unwrap());
+        match rule {
+            Rule::pattern => self.optional_list(pairs, |f, pair| f.token(&pair)),
+            _ => self.optional_list(pairs, Self::expr),
+        }
+    }
+}
+
+// Expressions.
+impl Formatter {
+    fn expr(&mut self, pair: Pair<'static, Rule>) {
+        let rule = pair.as_rule();
+        let text = pair.as_str();
+        let mut pairs = pair.clone().into_inner();
+
+        match rule {
+            Rule::expr | Rule::value => self.expr(pairs.next().unwrap()),
+            Rule::term => match pairs.next().unwrap() {
+                inner if text.starts_with('(') => {
+                    self.write("(");
+                    self.expr(inner);
+                    self.write(")");
+                }
+                inner => self.expr(inner),
+            },
+            Rule::binexpr => {
+                for pair in pairs {
+                    match pair.as_rule() {
+                        Rule::term => self.expr(pair),
+                        _ => {
+                            self.write(" ");
+                            self.token(&pair);
+                            self.write(" ");
+                        }
+                    }
+                }
+            }
+            Rule::unexpr => {
+                let op = pairs.next().unwrap();
+                let term = pairs.next().unwrap();
+                self.token(&op);
+                // `- -x` must not become `--x`, a decrement.
+                let text = term.as_str();
+                if matches!(op.as_str(), "+" | "-") && (text.starts_with(op.as_str()) || text.starts_with("++") || text.starts_with("--")) {
+                    self.write(" ");
+                }
+                self.expr(term);
+            }
+            Rule::ternary => {
+                self.write("(");
+                self.expr(pairs.next().unwrap());
+                self.write(") ? ");
+                self.expr(pairs.next().unwrap());
+                self.write(" : ");
+                self.expr(pairs.next().unwrap());
+            }
+            Rule::assign => {
+                self.lvalue(pairs.next().unwrap());
+                self.write(" ");
+                self.token(&pairs.next().unwrap());
+                self.write(" ");
+                self.expr(pairs.next().unwrap());
+            }
+            Rule::pre_incr => {
+                self.token(&pairs.next().unwrap());
+                self.lvalue(pairs.next().unwrap());
+            }
+            Rule::post_incr => {
+                self.lvalue(pairs.next().unwrap());
+                self.token(&pairs.next().unwrap());
+            }
+            Rule::lvalue => self.lvalue(pair),
+            Rule::atom => self.token(&pair),
+            Rule::enum_init => self.path(pair),
+            Rule::call => {
+                self.token(&pairs.next().unwrap());
+                self.group("(", pairs.collect(), pair.as_span().end(), ")", Separator::Comma);
+            }
+            Rule::list_init => self.group("[", pairs.collect(), pair.as_span().end(), "]", Separator::Comma),
+            Rule::struct_init => {
+                self.token(&pairs.next().unwrap());
+                self.group(" {", pairs.collect(), pair.as_span().end(), "}", Separator::Semicolon);
+            }
+            Rule::lambda => {
+                self.inline_comments(pair.as_span().start());
+                self.write("function");
+                let pairs = pairs.collect::<Vec<_>>();
+                let (body, mut signature) = pairs.split_last().unwrap();
+                if signature.first().map(Pair::as_rule) == Some(Rule::type_) {
+                    self.write(" ");
+                    self.token(&signature[0]);
+                    signature = &signature[1..];
+                }
+                self.params(signature);
+                self.block(body.clone());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn lvalue(&mut self, pair: Pair<'static, Rule>) {
+        for (i, pair) in pair.into_inner().enumerate() {
+            match pair.as_rule() {
+                Rule::ident => {
+                    if i != 0 {
+                        self.write(".");
+                    }
+                    self.token(&pair);
+                }
+                _ => for expr in pair.into_inner() {
+                    self.write("[");
+                    self.expr(expr);
+                    self.write("]");
+                },
+            }
+        }
+    }
+
+    // Writes the items of a call, a list or a struct initialization, on one line if they fit,
+    // or one per line otherwise. A single argument is kept on the line of the call.
+    fn group(&mut self, open: &str, pairs: Vec<Pair<'static, Rule>>, end: usize, close: &str, separator: Separator) {
+        // The items, made of a field name and of an expression for struct initializations.
+        let items = match separator {
+            Separator::Comma => pairs.into_iter().map(|pair| vec![pair]).collect::<Vec<_>>(),
+            Separator::Semicolon => pairs.chunks(2).map(<[_]>::to_vec).collect(),
+        };
+
+        if items.is_empty() {
+            self.write(open.trim_end());
+            self.open("");
+            return self.close(end - close.len(), close);
+        }
+
+        if separator == Separator::Comma && items.len() == 1 {
+            self.write(open);
+            self.expr(items[0][0].clone());
+            self.inline_comments(end - close.len());
+            self.trim();
+            self.write(close);
+            self.pos = end;
+            return;
+        }
+
+        // Struct initializations whose first field is on a new line stay split.
+        let split = separator == Separator::Semicolon && self.src[self.pos..items[0][0].as_span().start()].contains('\n');
+
+        // An anonymous function given as the last argument may span several lines, after the first one.
+        let hug = separator == Separator::Comma && is_function(&items.last().unwrap()[0]);
+
+        // Otherwise tries to write the group on the current line first.
+        if !split {
+            let (len, pos, inline) = (self.out.len(), self.pos, self.inline);
+            let mut last = len;
+            self.inline = true;
+            self.write(open);
+            if separator == Separator::Semicolon {
+                self.write(" ");
+            }
+            for (i, item) in items.iter().enumerate() {
+                if i != 0 && separator == Separator::Comma {
+                    self.write(", ");
+                }
+                last = self.out.len();
+                self.item(item.clone(), separator);
+                if separator == Separator::Semicolon {
+                    self.write(" ");
+                }
+            }
+            self.inline_comments(end - close.len());
+            if separator == Separator::Comma {
+                self.trim();
+            }
+            self.write(close);
+            self.pos = end;
+            self.inline = inline;
+
+            // The group doesn't fit if a group in it was split.
+            let first_line = len + self.out[len..].find('\n').unwrap_or(self.out.len() - len);
+            let fits = match hug {
+                true => !self.out[len..last].contains('\n') && self.column_at(first_line) <= WIDTH,
+                false => self.column() <= WIDTH && !self.out[len..].contains('\n'),
+            };
+            if inline || fits {
+                return;
+            }
+
+            self.out.truncate(len);
+            self.pos = pos;
+        }
+
+        // Puts each item on its own line if it doesn't fit.
+        self.open(open);
+        for item in items {
+            self.line(item[0].as_span().start(), Some(false));
+            self.item(item, separator);
+            if separator == Separator::Comma {
+                self.write(",");
+            }
+        }
+        self.close(end - close.len(), close);
+    }
+
+    // Writes an item of a group.
+    fn item(&mut self, item: Vec<Pair<'static, Rule>>, separator: Separator) {
+        let mut pairs = item.into_iter();
+        if separator == Separator::Semicolon {
+            self.token(&pairs.next().unwrap());
+            self.write(": ");
+        }
+        self.expr(pairs.next().unwrap());
+        if separator == Separator::Semicolon {
+            self.write(";");
+        }
+    }
+
+    // Writes the items of a list between parentheses, or nothing if there are none.
+    fn optional_list(&mut self, pairs: Pairs<'static, Rule>, item: impl FnMut(&mut Self, Pair<'static, Rule>)) {
+        if pairs.peek().is_some() {
+            self.write("(");
+            self.separated(pairs, ", ", item);
+            self.write(")");
+        }
+    }
+
+    // Writes items with a separator between them.
+    fn separated(&mut self, pairs: impl Iterator<Item = Pair<'static, Rule>>, separator: &str, mut item: impl FnMut(&mut Self, Pair<'static, Rule>)) {
+        for (i, pair) in pairs.enumerate() {
+            if i != 0 {
+                self.write(separator);
+            }
+            item(self, pair);
+        }
+    }
+}
+
+// Returns true if the expression is an anonymous function.
+fn is_function(pair: &Pair<'static, Rule>) -> bool {
+    let mut pair = pair.clone();
+    loop {
+        match pair.as_rule() {
+            Rule::lambda => return true,
+            Rule::expr | Rule::term | Rule::value => {
+                let mut pairs = pair.into_inner();
+                match (pairs.next(), pairs.next()) {
+                    (Some(inner), None) => pair = inner,
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        }
+    }
+}
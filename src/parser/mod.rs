@@ -1,17 +1,21 @@
 use anyhow::{anyhow,  Result};
 use pest::Parser;
+use pest::error::InputLocation;
 use pest::iterators::{Pair, Pairs};
 use pest_derive::Parser;
 
 use std::path::Path;
 
 use crate::ast;
-use crate::sources::Sources;
+use crate::sources::{Sources, WithSpan};
 use crate::utils::*;
 
 mod expressions;
 use expressions::*;
 
+mod format;
+pub use format::format;
+
 mod statements;
 use statements::*;
 
@@ -29,21 +33,39 @@ struct Grammar;
 // Generates the Abstract Syntax Tree from the program's source code. 
 pub fn parse_program(path: &Path) -> Result<&'static ast::AST> {
     let mut src = Sources::default();
+    let file = src.add(path)?.unwrap();
+    parse_root(path, Rule::program, file, src)
+}
+
+// Generates the Abstract Syntax Tree from source code held in memory.
+// Includes are resolved relatively to the given path.
+pub fn parse_source(path: &Path, code: String) -> Result<&'static ast::AST> {
+    let mut src = Sources::default();
+    let file = src.add_str(path, code);
+    parse_root(path, Rule::program, file, src)
+}
+
+// Parses a file opened in an editor, which may be a program or a module.
+// Includes are resolved relatively to the given path.
+pub fn parse_document(path: &Path, code: String) -> Result<&'static ast::AST> {
+    let mut src = Sources::default();
+    let file = src.add_str(path, code);
+    parse_root(path, Rule::document, file, src)
+}
 
-    let pairs = Grammar::parse(Rule::program, src.add(path)?.unwrap())?
-        .next().unwrap().into_inner();
+// Parses the root file of a program with the given rule.
+fn parse_root(path: &Path, rule: Rule, file: &'static str, mut src: Sources) -> Result<&'static ast::AST> {
+    let pairs = parse_file(rule, file)?;
 
     let mut ast = Box::new(ast::AST::default());
 
     for pair in pairs {
         match pair.as_rule() {
             Rule::include => parse_module(path, pair.into_inner(), &mut ast, &mut src)?,
-            Rule::element => {
-                let (name, element) = parse_element(pair.into_inner())?;
-                ast.names.insert(name, element);
-            }
+            Rule::element => add_element(&mut ast, pair)?,
             Rule::init => ast.init = parse_ident_list(pair.into_inner()),
             Rule::run => ast.run = parse_ident_list(pair.into_inner()),
+            Rule::shutdown => ast.shutdown = parse_ident_list(pair.into_inner()),
             Rule::EOI => (),
             _ => unreachable!(),
         }
@@ -52,21 +74,41 @@ pub fn parse_program(path: &Path) -> Result<&'static ast::AST> {
     Ok(Box::leak(ast))
 }
 
+// Parses code typed in the REPL, made of definitions that are returned in an AST and of
+// statements that are returned in a block. Includes are resolved relatively to the current directory.
+pub fn parse_input(code: String, src: &mut Sources) -> Result<(&'static ast::AST, &'static ast::Block)> {
+    let path = Path::new("<repl>");
+    let file = src.add_str(path, code);
+    let pairs = parse_file(Rule::input, file)?;
+
+    let mut ast = Box::new(ast::AST::default());
+    let mut block = Box::new(ast::Block::default());
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::include => parse_module(path, pair.into_inner(), &mut ast, src)?,
+            Rule::element => add_element(&mut ast, pair)?,
+            Rule::stmt => block.statements.push(parse_statement(pair)),
+            Rule::EOI => (),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok((Box::leak(ast), Box::leak(block)))
+}
+
 // Parse a module file.
 fn parse_module(root: &Path, mut pairs: Pairs<'static, Rule>, ast: &mut ast::AST, src: &mut Sources) -> Result<()> {
-    let path = root.parent().unwrap().join(parse_string(pairs.next().unwrap().as_str()));
+    let pair = pairs.next().unwrap();
+    let path = root.parent().unwrap().join(parse_string(pair.as_str()));
     
-    if let Some(file) = src.add(&path)? {
-        let pairs = Grammar::parse(Rule::module, file)?
-            .next().unwrap().into_inner();
+    if let Some(file) = src.add(&path).at(pair.as_span().into())? {
+        let pairs = parse_file(Rule::module, file)?;
 
         for pair in pairs {
             match pair.as_rule() {
                 Rule::include => parse_module(&path, pair.into_inner(), ast, src)?,
-                Rule::element => {
-                    let (name, element) = parse_element(pair.into_inner())?;
-                    ast.names.insert(name, element);
-                }
+                Rule::element => add_element(ast, pair)?,
                 Rule::EOI => (),
                 _ => unreachable!(),
             }
@@ -76,16 +118,61 @@ fn parse_module(root: &Path, mut pairs: Pairs<'static, Rule>, ast: &mut ast::AST
     Ok(())
 }
 
-// Parses an element.
-fn parse_element(mut pairs: Pairs<'static, Rule>) -> Result<(&'static str, ast::Name)> {
+// Parses a source file with the given rule, returning the pairs inside of it.
+fn parse_file(rule: Rule, file: &'static str) -> Result<Pairs<'static, Rule>> {
+    match Grammar::parse(rule, file) {
+        Ok(mut pairs) => Ok(pairs.next().unwrap().into_inner()),
+        Err(error) => {
+            let (start, end) = match error.location {
+                InputLocation::Pos(pos) => (pos, pos),
+                InputLocation::Span(span) => span,
+            };
+            let span = pest::Span::new(file, start, end).unwrap().into();
+            Err(anyhow!("Syntax error, {}.", error.variant.message())).at(span)
+        }
+    }
+}
+
+// Parses an element and adds it to the AST, checking that its name is not taken yet.
+fn add_element(ast: &mut ast::AST, pair: Pair<'static, Rule>) -> Result<()> {
+    let mut pairs = pair.into_inner().peekable();
+    let doc = pairs.next_if(|pair| pair.as_rule() == Rule::doc).map(|pair| parse_doc(pair.as_str()));
+
     let element = pairs.next().unwrap();
+    let span = element.as_span().into();
+    let (name, element) = parse_element(element).at(span)?;
+
+    if ast.names.insert(name, element).is_some() {
+        return Err(anyhow!("Object with name {} already exists.", name)).at(span);
+    }
+    ast.spans.insert(name, span);
+    if let Some(doc) = doc {
+        ast.docs.insert(name, doc);
+    }
 
+    Ok(())
+}
+
+// Removes the markers of a doc comment, and the stars at the start of its lines.
+fn parse_doc(doc: &str) -> String {
+    let doc = &doc[3..doc.len() - 2];
+    let lines = doc.lines()
+        .map(|line| line.trim())
+        .map(|line| line.strip_prefix('*').map_or(line, |line| line.strip_prefix(' ').unwrap_or(line)))
+        .collect::<Vec<_>>();
+    lines.join("\n").trim().to_string()
+}
+
+// Parses an element.
+fn parse_element(element: Pair<'static, Rule>) -> Result<(&'static str, ast::Name)> {
     match element.as_rule() {
         Rule::component => parse_component(element.into_inner()),
         Rule::resource => parse_resource(element.into_inner()),
+        Rule::event => parse_event(element.into_inner()),
         Rule::struct_ => parse_struct(element.into_inner()),
+        Rule::enum_ => parse_enum(element.into_inner()),
         Rule::function => Ok(parse_function(element.into_inner())),
-        Rule::system => Ok(parse_system(element.into_inner())),
+        Rule::system => Ok(parse_system(element)),
         _ => unreachable!(),
     }
 }
@@ -104,6 +191,13 @@ fn parse_resource(mut pairs: Pairs<'static, Rule>) -> Result<(&'static str, ast:
     Ok((name, ast::Name::Resource(def)))
 }
 
+// Parses an event definition.
+fn parse_event(mut pairs: Pairs<'static, Rule>) -> Result<(&'static str, ast::Name)> {
+    let name = pairs.next().unwrap().as_str();
+    let def = parse_struct_def(pairs.next().unwrap().into_inner())?;
+    Ok((name, ast::Name::Event(def)))
+}
+
 // Parses a componenet definition.
 fn parse_struct(mut pairs: Pairs<'static, Rule>) -> Result<(&'static str, ast::Name)> {
     let name = pairs.next().unwrap().as_str();
@@ -111,17 +205,41 @@ fn parse_struct(mut pairs: Pairs<'static, Rule>) -> Result<(&'static str, ast::N
     Ok((name, ast::Name::Struct(def)))
 }
 
+// Parses an enum definition.
+fn parse_enum(mut pairs: Pairs<'static, Rule>) -> Result<(&'static str, ast::Name)> {
+    let name = pairs.next().unwrap().as_str();
+    let def = parse_enum_def(pairs)?;
+    Ok((name, ast::Name::Enum(def)))
+}
+
 // Parses a function definition.
-fn parse_function(mut pairs: Pairs<'static, Rule>) -> (&'static str, ast::Name) {
+fn parse_function(pairs: Pairs<'static, Rule>) -> (&'static str, ast::Name) {
+    let mut pairs = pairs.peekable();
+    let returns = pairs.next_if(|pair| pair.as_rule() == Rule::type_);
     let name = pairs.next().unwrap().as_str();
+
+    let mut def = parse_function_def(pairs);
+    def.returns = returns.map(|ty| parse_type(ty.into_inner()));
+    (name, ast::Name::Function(def))
+}
+
+// Parses the return type, the parameters and the body of a function, named or not.
+pub fn parse_function_def(pairs: impl Iterator<Item = Pair<'static, Rule>>) -> ast::Function {
+    let mut returns = None;
     let mut args = Vec::new();
+    let mut types = Vec::new();
 
     for pair in pairs {
         match pair.as_rule() {
-            Rule::ident => args.push(pair.as_str()),
-            Rule::block => return (name, ast::Name::Function(ast::Function {
-                args, body: parse_block(pair.into_inner()),
-            })),
+            Rule::type_ => returns = Some(parse_type(pair.into_inner())),
+            Rule::param => {
+                let mut pairs = pair.into_inner().rev();
+                args.push(pairs.next().unwrap().as_str());
+                types.push(pairs.next().map(|ty| parse_type(ty.into_inner())));
+            }
+            Rule::block => return ast::Function {
+                args, types, returns, body: parse_block(pair.into_inner()),
+            },
             _ => unreachable!(),
         }
     };
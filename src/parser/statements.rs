@@ -1,44 +1,52 @@
 use super::*;
 
 // Parses a statement.
-pub fn parse_statement(mut pairs: Pairs<'static, Rule>) -> ast::Statement {
-    let pair = pairs.next().unwrap();
-
-    match pair.as_rule() {
-        Rule::decl => ast::Statement::Decl(parse_decl(pair.into_inner())),
-        Rule::if_ => ast::Statement::If(parse_if(pair.into_inner())),
-        Rule::for_ => ast::Statement::For(parse_for(pair.into_inner())),
-        Rule::while_ => ast::Statement::While(parse_while(pair.into_inner())),
-        Rule::query => ast::Statement::Query(parse_query(pair.into_inner())),
-        Rule::switch => ast::Statement::Switch(parse_switch(pair.into_inner())),
-        Rule::block => ast::Statement::Block(parse_block(pair.into_inner())),
-        Rule::expr => ast::Statement::Expr(parse_expr(pair.into_inner())),
-        Rule::break_ => ast::Statement::Break,
-        Rule::continue_ => ast::Statement::Continue,
-        Rule::return_ => ast::Statement::Return(parse_return(pair.into_inner())),
+pub fn parse_statement(pair: Pair<'static, Rule>) -> ast::Statement {
+    let span = pair.as_span().into();
+
+    // An empty statement does nothing, like an empty block.
+    let pair = match pair.into_inner().next() {
+        Some(pair) => pair,
+        None => return ast::Statement {kind: ast::StatementKind::Block(ast::Block::default()), span},
+    };
+
+    let kind = match pair.as_rule() {
+        Rule::decl => ast::StatementKind::Decl(parse_decl(pair.into_inner())),
+        Rule::if_ => ast::StatementKind::If(parse_if(pair.into_inner())),
+        Rule::for_ => ast::StatementKind::For(parse_for(pair.into_inner())),
+        Rule::while_ => ast::StatementKind::While(parse_while(pair.into_inner())),
+        Rule::query => ast::StatementKind::Query(parse_query(pair.into_inner())),
+        Rule::switch => ast::StatementKind::Switch(parse_switch(pair.into_inner())),
+        Rule::block => ast::StatementKind::Block(parse_block(pair.into_inner())),
+        Rule::expr => ast::StatementKind::Expr(parse_expr(pair)),
+        Rule::break_ => ast::StatementKind::Break,
+        Rule::continue_ => ast::StatementKind::Continue,
+        Rule::return_ => ast::StatementKind::Return(parse_return(pair.into_inner())),
         _ => unreachable!(),
-    }
+    };
+
+    ast::Statement {kind, span}
 }
 
 // Parses a declaration.
 pub fn parse_decl(mut pairs: Pairs<'static, Rule>) -> ast::Decl {
     ast::Decl {
         ident: pairs.next().unwrap().as_str(),
-        init: pairs.next().map(|pair| parse_expr(pair.into_inner())),
+        init: pairs.next().map(parse_expr),
     }
 }
 
 // Parses a block.
 pub fn parse_block(pairs: Pairs<'static, Rule>) -> ast::Block {
     ast::Block {
-        statements: pairs.map(|pair| parse_statement(pair.into_inner())).collect()
+        statements: pairs.map(parse_statement).collect()
     }
 }
 
 // Parses a if.
 pub fn parse_if(mut pairs: Pairs<'static, Rule>) -> ast::If {
     ast::If {
-        cond: parse_expr(pairs.next().unwrap().into_inner()),
+        cond: parse_expr(pairs.next().unwrap()),
         branch1: parse_block(pairs.next().unwrap().into_inner()),
         branch2: pairs.next().map(|pair| parse_block(pair.into_inner())),
     }
@@ -50,13 +58,13 @@ pub fn parse_for(mut pairs: Pairs<'static, Rule>) -> ast::For {
         init: {
             let pair = pairs.next().unwrap();
             match pair.as_rule() {
-                Rule::expr => Either::Left(parse_expr(pair.into_inner())),
+                Rule::expr => Either::Left(parse_expr(pair)),
                 Rule::decl => Either::Right(parse_decl(pair.into_inner())),
                 _ => unreachable!(),
             }
         },
-        cond: parse_expr(pairs.next().unwrap().into_inner()),
-        incr: parse_expr(pairs.next().unwrap().into_inner()),
+        cond: parse_expr(pairs.next().unwrap()),
+        incr: parse_expr(pairs.next().unwrap()),
         code: parse_block(pairs.next().unwrap().into_inner())
     }
 }
@@ -64,7 +72,7 @@ pub fn parse_for(mut pairs: Pairs<'static, Rule>) -> ast::For {
 // Parses a while loop.
 pub fn parse_while(mut pairs: Pairs<'static, Rule>) -> ast::While {
     ast::While {
-        cond: parse_expr(pairs.next().unwrap().into_inner()),
+        cond: parse_expr(pairs.next().unwrap()),
         code: parse_block(pairs.next().unwrap().into_inner()),
     }
 }
@@ -79,28 +87,50 @@ pub fn parse_query(mut pairs: Pairs<'static, Rule>) -> ast::Query {
 
 // Parses a switch block.
 pub fn parse_switch(mut pairs: Pairs<'static, Rule>) -> ast::Switch {
-    let expr = parse_expr(pairs.next().unwrap().into_inner());
-    let mut cases = Vec::new();
+    let mut switch = ast::Switch {
+        expr: parse_expr(pairs.next().unwrap()),
+        cases: Vec::new(),
+        default: None,
+    };
 
-    while let Some(pair) = pairs.next() {
+    for pair in pairs {
         match pair.as_rule() {
-            Rule::atom => cases.push(ast::SwitchCase {
-                val: parse_atom(pair.into_inner()),
-                block: parse_block(pairs.next().unwrap().into_inner()),
-            }),
-            Rule::block => return ast::Switch {
-                expr,
-                cases,
-                default: parse_block(pair.into_inner()),
-            },
+            Rule::case => switch.cases.push(parse_case(pair)),
+            Rule::default_ => switch.default = Some(parse_block(pair.into_inner().next().unwrap().into_inner())),
             _ => unreachable!(),
         }
     }
 
-    unreachable!()
+    switch
+}
+
+// Parses a switch case.
+pub fn parse_case(pair: Pair<'static, Rule>) -> ast::SwitchCase {
+    let span = pair.as_span().into();
+    let mut pairs = pair.into_inner();
+    let pattern = pairs.next().unwrap();
+
+    ast::SwitchCase {
+        pattern: match pattern.as_rule() {
+            Rule::atom => ast::Pattern::Atom(parse_atom(pattern)),
+            Rule::pattern => ast::Pattern::Variant(parse_pattern(pattern.into_inner())),
+            _ => unreachable!(),
+        },
+        block: parse_block(pairs.next().unwrap().into_inner()),
+        span,
+    }
+}
+
+// Parses a pattern matching a variant of an enum.
+pub fn parse_pattern(mut pairs: Pairs<'static, Rule>) -> ast::VariantPattern {
+    ast::VariantPattern {
+        name: pairs.next().unwrap().as_str(),
+        variant: pairs.next().unwrap().as_str(),
+        bindings: pairs.map(|pair| pair.as_str()).collect(),
+    }
 }
 
 // Parses a return statement.
 pub fn parse_return(mut pairs: Pairs<'static, Rule>) -> Option<ast::Expr> {
-    pairs.next().map(|pair| parse_expr(pair.into_inner()))
+    pairs.next().map(parse_expr)
 }
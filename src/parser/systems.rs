@@ -1,7 +1,9 @@
 use super::*;
 
 // Parses a system.
-pub fn parse_system(mut pairs: Pairs<'static, Rule>) -> (&'static str, ast::Name) {
+pub fn parse_system(pair: Pair<'static, Rule>) -> (&'static str, ast::Name) {
+    let span = pair.as_span().into();
+    let mut pairs = pair.into_inner();
     let name = pairs.next().unwrap().as_str();
 
     let pair = pairs.next().unwrap();
@@ -10,10 +12,12 @@ pub fn parse_system(mut pairs: Pairs<'static, Rule>) -> (&'static str, ast::Name
         Rule::filter => ast::System {
             filter: parse_filter(pair.into_inner()),
             code: parse_block(pairs.next().unwrap().into_inner()),
+            span,
         },
         Rule::block => ast::System {
             filter: ast::Filter::default(),
             code: parse_block(pair.into_inner()),
+            span,
         },
         _ => unreachable!(),
     }))
@@ -29,7 +33,10 @@ pub fn parse_filter(pairs: Pairs<'static, Rule>) -> ast::Filter {
                 parse_entity_filter(pair.into_inner())
             ),
             Rule::arg => filter.resources.push(
-                parse_argument(pair.into_inner())
+                parse_argument(pair)
+            ),
+            Rule::events => filter.events.push(
+                parse_argument(pair)
             ),
             _ => unreachable!(),
         }
@@ -40,20 +47,36 @@ pub fn parse_filter(pairs: Pairs<'static, Rule>) -> ast::Filter {
 
 // Parses an entity filter.
 pub fn parse_entity_filter(mut pairs: Pairs<'static, Rule>) -> ast::EntityFilter {
-    let name = pairs.next().unwrap().as_str();
-    let mut args = Vec::new();
+    let mut filter = ast::EntityFilter {
+        name: pairs.next().unwrap().as_str(),
+        args: Vec::new(),
+        without: Vec::new(),
+        optional: Vec::new(),
+        added: Vec::new(),
+        changed: Vec::new(),
+    };
 
     for pair in pairs {
-        args.push(parse_argument(pair.into_inner()));
+        match pair.as_rule() {
+            Rule::arg => filter.args.push(parse_argument(pair)),
+            Rule::without => filter.without.push(pair.into_inner().as_str()),
+            Rule::optional => filter.optional.push(parse_argument(pair)),
+            Rule::added => filter.added.push(pair.into_inner().as_str()),
+            Rule::changed => filter.changed.push(pair.into_inner().as_str()),
+            _ => unreachable!(),
+        }
     }
 
-    ast::EntityFilter {name, args}
+    filter
 }
 
 // Parses a formal argument to a function or system call.
-pub fn parse_argument(mut pairs: Pairs<'static, Rule>) -> ast::Argument {
+pub fn parse_argument(pair: Pair<'static, Rule>) -> ast::Argument {
+    let span = pair.as_span().into();
+    let mut pairs = pair.into_inner();
     ast::Argument {
         ty: pairs.next().unwrap().as_str(), 
         name: pairs.next().unwrap().as_str(),
+        span,
     }
 }
\ No newline at end of file
@@ -10,6 +10,8 @@ pub fn parse_type(mut pairs: Pairs<'static, Rule>) -> ast::Type {
         Rule::char_t => ast::Type::Char,
         Rule::string_t => ast::Type::String,
         Rule::list_t => ast::Type::List,
+        Rule::entity_t => ast::Type::Entity,
+        Rule::function_t => ast::Type::Function,
         Rule::ident => ast::Type::Struct(pair.as_str()),
         _ => unreachable!(),
     }
@@ -21,6 +23,7 @@ pub fn parse_struct_def(mut pairs: Pairs<'static, Rule>) -> Result<ast::StructDe
     
     while let Some(ty) = pairs.next() {
         let name = pairs.next().unwrap().as_str();
+        def.spans.insert(name, ty.as_span().into());
         let ty = parse_type(ty.into_inner());
         if def.fields.insert(name, ty).is_some() {
             return Err(anyhow!("Duplicate field name: {}.", name));
@@ -28,4 +31,26 @@ pub fn parse_struct_def(mut pairs: Pairs<'static, Rule>) -> Result<ast::StructDe
     }
 
     Ok(def)
-}
\ No newline at end of file
+}
+// Parses an enum definition.
+pub fn parse_enum_def(pairs: Pairs<'static, Rule>) -> Result<ast::EnumDef> {
+    let mut def = ast::EnumDef::default();
+
+    for pair in pairs {
+        let span = pair.as_span().into();
+        let mut pairs = pair.into_inner();
+        let name = pairs.next().unwrap().as_str();
+
+        if def.variant(name).is_some() {
+            return Err(anyhow!("Duplicate variant name: {}.", name));
+        }
+
+        def.variants.push(ast::Variant {
+            name,
+            fields: pairs.map(|pair| parse_type(pair.into_inner())).collect(),
+            span,
+        });
+    }
+
+    Ok(def)
+}
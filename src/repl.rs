@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Error, Result};
+
+use std::io::{self, BufRead, Write};
+
+use crate::ast;
+use crate::check;
+use crate::eval::{self, Context, Def, Scope, Var};
+use crate::interpreter::Value;
+use crate::parser;
+use crate::sources::Sources;
+use crate::utils::*;
+
+// The help printed by the :help command.
+const HELP: &str = "\
+Type definitions or statements to run them. Blocks can span several lines.
+
+:entities         Prints the entities and their components.
+:resources        Prints the resources.
+:run SYSTEM [N]   Runs a system once, or N times.
+:help             Prints this help.
+:quit             Quits the REPL.";
+
+/// An interactive session, running C* code as it is typed.
+///
+/// Each input may define components, resources, structs, enums, functions and
+/// systems, and contain statements that are run right away. The world and the
+/// variables declared by statements are kept from one input to the next.
+/// Functions and systems can be redefined, other names can't.
+///
+/// ```
+/// use cstar::{Repl, Value};
+///
+/// let mut repl = Repl::new();
+/// repl.eval("Resource Counter { int value; }")?;
+/// repl.eval("NewResource(Counter { value: 1; });")?;
+/// repl.eval("System count(Counter c) { c.value += 1; }")?;
+/// repl.run_system("count")?;
+///
+/// let value = repl.eval("let n = 2 * 3;\nn + 1")?;
+/// assert_eq!(value, Some(Value::Int(7)));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Repl {
+    ctx: Context,
+    // The variables declared by the statements typed so far.
+    scope: Scope,
+    sources: Sources,
+    warnings: Vec<Error>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl::new()
+    }
+}
+
+impl Repl {
+    /// Creates a session, with an empty world.
+    pub fn new() -> Repl {
+        Repl {
+            ctx: Context::new(Box::leak(Box::default())),
+            scope: Scope::default(),
+            sources: Sources::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Adds the definitions of an input, then runs its statements. Returns the value
+    /// of the last statement if it is an expression, unless it is void.
+    ///
+    /// The final semicolon of the input may be omitted. Definitions and statements
+    /// are checked before anything runs, and nothing is defined if there are errors.
+    pub fn eval(&mut self, input: &str) -> Result<Option<Value>> {
+        self.warnings.clear();
+
+        let (ast, block) = match parser::parse_input(input.to_string(), &mut self.sources) {
+            Ok(parsed) => parsed,
+            Err(err) => parser::parse_input(format!("{};", input.trim_end()), &mut self.sources).map_err(|_| err)?,
+        };
+
+        let defs = self.ctx.defs();
+        self.define(ast)?;
+        if let Err(err) = check::check_statements(block, &self.ctx, self.scope.names(), &mut self.warnings) {
+            self.ctx.set_defs(defs);
+            return Err(err);
+        }
+
+        // An error leaves the scope levels of the statements it stopped in, and the commands
+        // of the input, which are dropped.
+        let depth = self.scope.depth();
+        let result = eval::eval_input(&self.ctx, &self.scope, block).inspect_err(|_| {
+            self.scope.truncate(depth);
+            self.ctx.discard_commands();
+        });
+
+        Ok(match result? {
+            Some(Var::Void) | None => None,
+            Some(var) => Some((&var).into()),
+        })
+    }
+
+    // Adds the names of the AST to the definitions, and checks them.
+    fn define(&mut self, ast: &'static ast::AST) -> Result<()> {
+        if ast.names.is_empty() {
+            return Ok(());
+        }
+
+        let old = self.ctx.defs();
+        let mut defs = old.clone();
+        for (name, element) in ast.names.iter() {
+            let def = eval::definition(element);
+            match (defs.get(name), &def) {
+                (None, _) | (Some(Def::Function(_)), Def::Function(_)) | (Some(Def::System(_)), Def::System(_)) => (),
+                _ => return Err(anyhow!("Object with name {} already exists.", name)),
+            }
+            defs.insert(name, def);
+        }
+
+        self.ctx.set_defs(Box::leak(Box::new(defs)));
+        if let Err(err) = check::check(ast, &self.ctx, &mut self.warnings) {
+            self.ctx.set_defs(old);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Returns the warnings found when checking the last input.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Runs the system with the given name once, as a new frame: the events sent
+    /// before the previous one are dropped.
+    pub fn run_system(&mut self, name: &str) -> Result<()> {
+        self.ctx.next_step();
+        eval::run_system(&self.ctx, name).inspect_err(|_| self.ctx.discard_commands())
+    }
+
+    /// Returns the exit code requested by a script with `Exit(code)`, if any.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.ctx.exit_code()
+    }
+
+    /// Reads inputs line by line until the end of the input, `:quit` or a call to
+    /// `Exit`, printing the results. An input ends at the first line where all the
+    /// blocks it opened are closed. Returns the exit code requested, or 0.
+    pub fn run(&mut self, input: impl BufRead) -> Result<i32> {
+        let mut lines = input.lines();
+        let mut buffer = String::new();
+
+        loop {
+            print!("{}", if buffer.is_empty() {"> "} else {". "});
+            io::stdout().flush()?;
+
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+
+            if buffer.is_empty() && line.trim_start().starts_with(':') {
+                match self.command(line.trim()) {
+                    Ok(true) => (),
+                    Ok(false) => return Ok(0),
+                    Err(err) => eprintln!("error: {:#}", err),
+                }
+            } else {
+                buffer.push_str(&line);
+                buffer.push('\n');
+                if is_complete(&buffer) {
+                    self.print_eval(&buffer);
+                    buffer.clear();
+                }
+            }
+
+            if let Some(code) = self.exit_code() {
+                return Ok(code);
+            }
+        }
+
+        // Reports the errors of an unfinished input.
+        if !buffer.trim().is_empty() {
+            self.print_eval(&buffer);
+        }
+
+        println!();
+        Ok(self.exit_code().unwrap_or(0))
+    }
+
+    // Runs an input, printing its value, the warnings and the error if any.
+    fn print_eval(&mut self, input: &str) {
+        if input.trim().is_empty() {
+            return;
+        }
+
+        let result = self.eval(input);
+        for warning in self.warnings() {
+            eprintln!("warning: {:#}\n", warning);
+        }
+        match result {
+            Ok(Some(value)) => println!("{}", value),
+            Ok(None) => (),
+            Err(err) => eprintln!("error: {:#}", err),
+        }
+    }
+
+    // Runs a command, returning false if the REPL should quit.
+    fn command(&mut self, line: &str) -> Result<bool> {
+        let mut words = line.split_whitespace();
+        match (words.next().unwrap_or(""), words.next(), words.next(), words.next()) {
+            (":help", None, _, _) => println!("{}", HELP),
+            (":quit", None, _, _) => return Ok(false),
+            (":entities", None, _, _) => {
+                let world = self.ctx.world();
+                let entities = world.entities();
+                if entities.is_empty() {
+                    println!("No entity.");
+                }
+                for entity in entities {
+                    let mut components = world.get_components(&entity)?.iter().collect::<Vec<_>>();
+                    components.sort_by_key(|(name, _)| **name);
+                    let components = components.into_iter()
+                        .map(|(name, var)| format!("{} {}", name, var))
+                        .collect::<Vec<_>>();
+                    println!("{}: {}", entity, components.join(", "));
+                }
+            }
+            (":resources", None, _, _) => {
+                let resources = self.ctx.world().resources();
+                if resources.is_empty() {
+                    println!("No resource.");
+                }
+                for (name, var) in resources {
+                    println!("{} {}", name, var);
+                }
+            }
+            (":run", Some(name), times, None) => {
+                let times = match times {
+                    Some(times) => times.parse::<usize>().map_err(|_| anyhow!("Expected a number of times, got {}.", times))?,
+                    None => 1,
+                };
+                for _ in 0..times {
+                    if self.exit_code().is_some() {
+                        break;
+                    }
+                    self.run_system(name)?;
+                }
+            }
+            (":run", ..) => return Err(anyhow!("Usage: :run SYSTEM [N]")),
+            (command, ..) => return Err(anyhow!("Unknown command {}, type :help for the list of commands.", command)),
+        }
+        Ok(true)
+    }
+}
+
+// Returns true if all the blocks, parentheses and brackets opened in the input are closed,
+// ignoring the ones in strings, chars and comments.
+fn is_complete(input: &str) -> bool {
+    let mut depth = 0i32;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            '"' | '\'' => loop {
+                match chars.next() {
+                    Some('\\') => { chars.next(); },
+                    Some(end) if end == c => break,
+                    Some(_) => (),
+                    None => return false,
+                }
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut star = false;
+                loop {
+                    match chars.next() {
+                        Some('/') if star => break,
+                        Some(c) => star = c == '*',
+                        None => return false,
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            _ => (),
+        }
+    }
+
+    depth <= 0
+}
@@ -1,8 +1,24 @@
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
+use lazy_static::lazy_static;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::{PathBuf, Path};
+use std::sync::Mutex;
+
+lazy_static! {
+    // All the source files ever read, with their paths. Used to find back
+    // which file a span points into.
+    static ref FILES: Mutex<Files> = Mutex::new(Files::default());
+}
+
+// The source files read, by the address they start at and by path.
+#[derive(Default)]
+struct Files {
+    starts: BTreeMap<usize, (PathBuf, &'static str)>,
+    paths: HashMap<PathBuf, HashSet<&'static str>>,
+}
 
 // A structs that holds the names of the source filed already parsed.
 #[derive(Default, Debug)]
@@ -16,9 +32,143 @@ impl Sources {
     // the file wasn't read already, or None if it was.
     pub fn add(&mut self, path: &Path) -> Result<Option<&'static str>> {
         self.src.insert(path.to_path_buf())
-            .then(|| fs::read_to_string(&path)
-                .map_err(Error::from)
-                .map(|s| &*Box::leak(String::into_boxed_str(s))))
+            .then(|| fs::read_to_string(path)
+                .map_err(|err| anyhow!("Could not read {}: {}.", path.display(), err))
+                .map(|s| register(path, s)))
             .transpose()
     }
-}
\ No newline at end of file
+
+    // Adds a source whose code is already known, returning a static reference to it.
+    pub fn add_str(&mut self, path: &Path, code: String) -> &'static str {
+        self.src.insert(path.to_path_buf());
+        register(path, code)
+    }
+}
+
+// Leaks the given source code and remembers the path it was read from. The same code read
+// again from the same path isn't leaked twice.
+pub fn register(path: &Path, src: String) -> &'static str {
+    let mut files = FILES.lock().unwrap();
+    if let Some(file) = files.paths.get(path).and_then(|files| files.get(src.as_str())) {
+        return file;
+    }
+    let src = &*Box::leak(String::into_boxed_str(src));
+    files.starts.insert(src.as_ptr() as usize, (path.to_path_buf(), src));
+    files.paths.entry(path.to_path_buf()).or_default().insert(src);
+    src
+}
+
+// Finds the path of the file the given string slice was taken from.
+fn path_of(s: &str) -> Option<PathBuf> {
+    let ptr = s.as_ptr() as usize;
+    let files = FILES.lock().unwrap();
+    let (start, (path, src)) = files.starts.range(..=ptr).next_back()?;
+    (ptr <= start + src.len()).then(|| path.clone())
+}
+
+// =============================================================== Span
+
+// A location in the source code.
+#[derive(Clone, Copy)]
+pub struct Span(pest::Span<'static>);
+
+impl Span {
+    // Returns the span covering both self and other.
+    pub fn to(&self, other: Span) -> Span {
+        Span(self.0.start_pos().span(&other.0.end_pos()))
+    }
+
+    // Returns the source code covered by this span.
+    pub fn as_str(&self) -> &'static str {
+        self.0.as_str()
+    }
+
+    // Returns the offsets in bytes of the start and of the end of the span.
+    pub fn offsets(&self) -> (usize, usize) {
+        (self.0.start(), self.0.end())
+    }
+
+    // Returns the whole source code the span is in.
+    pub fn source(&self) -> &'static str {
+        self.0.get_input()
+    }
+
+    // Returns the path of the file this span is in.
+    pub fn path(&self) -> Option<PathBuf> {
+        path_of(self.0.get_input())
+    }
+
+    // Returns the line and column at which the span starts.
+    pub fn line_col(&self) -> (usize, usize) {
+        self.0.start_pos().line_col()
+    }
+}
+
+impl From<pest::Span<'static>> for Span {
+    fn from(span: pest::Span<'static>) -> Self {
+        Span(span)
+    }
+}
+
+impl fmt::Display for Span {
+    // Prints the location as path:line:col.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, col) = self.line_col();
+        match self.path() {
+            Some(path) => write!(f, "{}:{}:{}", path.display(), line, col),
+            None => write!(f, "{}:{}", line, col),
+        }
+    }
+}
+
+impl fmt::Debug for Span {
+    // Spans are printed compactly, to keep the AST dump readable.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+// =============================================================== SpanError
+
+// An error that occured at some location of the source code.
+#[derive(Debug)]
+pub struct SpanError {
+    pub span: Span,
+    pub error: Error,
+}
+
+impl fmt::Display for SpanError {
+    // Prints the error, followed by the location and a caret-underlined snippet of the code.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, col) = self.span.line_col();
+        let text = self.span.0.start_pos().line_of().trim_end_matches(['\r', '\n']);
+
+        // Underline until the end of the span or of the line, whichever comes first.
+        let len = self.span.as_str().lines().next().unwrap_or("").chars().count().max(1);
+        let len = len.min(text.chars().count().saturating_sub(col - 1).max(1));
+
+        let pad = " ".repeat(line.to_string().len());
+        writeln!(f, "{:#}", self.error)?;
+        writeln!(f, "{}--> {}", pad, self.span)?;
+        writeln!(f, "{} |", pad)?;
+        writeln!(f, "{} | {}", line, text)?;
+        write!(f, "{} | {}{}", pad, " ".repeat(col - 1), "^".repeat(len))
+    }
+}
+
+impl std::error::Error for SpanError {}
+
+// Adds location information to errors.
+pub trait WithSpan<T> {
+    // Attaches the span to the error, if it isn't located yet.
+    fn at(self, span: Span) -> Result<T>;
+}
+
+impl<T> WithSpan<T> for Result<T> {
+    fn at(self, span: Span) -> Result<T> {
+        self.map_err(|error| match error.is::<SpanError>() {
+            true => error,
+            false => SpanError {span, error}.into(),
+        })
+    }
+}
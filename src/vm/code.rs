@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use super::*;
+
+// An instruction of the stack machine.
+#[derive(Clone, Copy, Debug)]
+pub enum Op {
+    // Pushes a constant of the chunk.
+    Const(usize),
+    // Pushes the value of a variable.
+    Load(usize),
+    // Pops a value into a newly declared variable.
+    Decl(usize),
+    // Fails, the variable with that name doesn't exist.
+    Undefined(&'static str),
+    Pop,
+    Dup,
+    Swap,
+    // Pops the given number of values into a list.
+    List(usize),
+    // Pops the values of the fields into a struct, in the order of the initialization.
+    Struct(&'static ast::StructInit),
+    // Checks the type of the value given to a field of a struct.
+    FieldType(&'static str, &'static str, ast::Type),
+    // Pops the given number of values into a variant of an enum.
+    Enum(&'static str, &'static str, usize),
+    // Checks the type of a value held by a variant of an enum.
+    PayloadType(&'static str, &'static str, ast::Type),
+    BinOp(ast::BinOp),
+    UnOp(ast::UnOp),
+    // Pops an index and a list or a string, and pushes the item.
+    Index,
+    // Pops a struct and pushes one of its fields.
+    Field(&'static str),
+    // Pushes a variable on the stack of places.
+    PlaceLocal(usize),
+    // Fails if the top of the stack isn't a list.
+    CheckList,
+    // Pops an index, a list and the variable the path started from, and pushes the item on the stack of places.
+    PlaceIndex,
+    // Pops a struct and the variable the path started from, and pushes one of its fields on the stack of places.
+    PlaceField(&'static str),
+    // Pushes the value of the topmost place.
+    PlaceGet,
+    // Pops the topmost place and sets it to the top of the stack.
+    PlaceSet,
+    // Pushes the incremented or decremented top of the stack, keeping the old value below it.
+    Incr(ast::IncrOp),
+    Jump(usize),
+    // Pops a condition and jumps if it is false. Fails if it isn't a boolean, in the given construct.
+    Test(usize, &'static str),
+    // Pops the top of the stack if it is equal to a constant, jumps otherwise.
+    Case(usize, usize),
+    // Pops the top of the stack if it matches a variant pattern, jumps otherwise.
+    CaseVariant(usize, usize),
+    // Checks the type of the argument with the given index given to a function.
+    ArgType(&'static str, usize, ast::Type),
+    // Calls the function with the given index, with the given number of arguments.
+    Call(usize, usize),
+    // Creates the anonymous function with the given index in the chunk, capturing its variables.
+    Closure(usize),
+    // Calls the function value below the given number of arguments, held by the named variable.
+    CallValue(&'static str, usize),
+    // Checks the type of the value returned by a function.
+    ReturnType(&'static str, ast::Type),
+    // Calls the native function with the given index, with the given number of arguments.
+    Native(usize, usize),
+    Return,
+    Break,
+    Continue,
+    // Starts iterating over the entities matched by a query.
+    QueryStart(usize),
+    // Binds the next entity of the query, or jumps if there is none left.
+    QueryNext(usize, usize),
+    // Stops iterating over the entities of the current query.
+    QueryEnd,
+}
+
+// The variables an entity and its components are bound to.
+#[derive(Debug)]
+pub struct Binding {
+    pub filter: &'static ast::EntityFilter,
+    pub entity: usize,
+    pub args: Vec<usize>,
+    pub optional: Vec<usize>,
+}
+
+// The compiled code of a function, a system or a query.
+#[derive(Default, Debug)]
+pub struct Chunk {
+    pub ops: Vec<Op>,
+    // The location of each instruction in the source code, for the errors.
+    pub spans: Vec<ast::Span>,
+    pub consts: Vec<Var>,
+    pub patterns: Vec<(&'static ast::VariantPattern, Vec<usize>)>,
+    pub queries: Vec<Binding>,
+    // The anonymous functions created by the chunk, with the names and the slots of the variables they capture.
+    pub closures: Vec<(&'static ast::Function, Vec<(&'static str, usize)>)>,
+    // The number of variables used by the chunk.
+    pub slots: usize,
+}
+
+// A compiled system.
+#[derive(Debug)]
+pub struct SystemCode {
+    pub sys: &'static ast::System,
+    pub resources: Vec<usize>,
+    pub events: Vec<usize>,
+    pub entities: Option<Binding>,
+    pub chunk: Chunk,
+}
+
+// A compiled program.
+#[derive(Debug)]
+pub struct Program {
+    pub functions: Vec<Chunk>,
+    // The index of the chunk of each function, named or anonymous, by the address of its definition.
+    pub chunks: HashMap<*const ast::Function, usize>,
+    pub natives: Vec<(&'static str, Native)>,
+    pub systems: Map<SystemCode>,
+}
@@ -0,0 +1,674 @@
+use std::collections::HashMap;
+
+use super::*;
+
+// Compiles all the functions and systems of a checked program.
+pub fn compile(ast: &'static ast::AST, ctx: &Context) -> Result<Program> {
+    // Functions are numbered first, so that calls can refer to any of them.
+    let functions = ast.names.iter()
+        .filter_map(|(name, element)| match element {
+            ast::Name::Function(def) => Some((*name, def)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut globals = Globals {
+        ctx,
+        functions: functions.iter().enumerate().map(|(i, (name, _))| (*name, i)).collect(),
+        natives: Vec::new(),
+        native_ids: Map::new(),
+        closures: Vec::new(),
+    };
+
+    let mut program = Program {
+        functions: Vec::with_capacity(functions.len()),
+        chunks: HashMap::new(),
+        natives: Vec::new(),
+        systems: Map::new(),
+    };
+
+    for (_, def) in functions {
+        program.chunks.insert(def as *const _, program.functions.len());
+        program.functions.push(compile_function(&mut globals, def)?);
+    }
+
+    for (name, element) in ast.names.iter() {
+        if let ast::Name::System(sys) = element {
+            program.systems.insert(name, compile_system(&mut globals, sys)?);
+        }
+    }
+
+    // Anonymous functions come after the named ones.
+    for (def, chunk) in globals.closures {
+        program.chunks.insert(def as *const _, program.functions.len());
+        program.functions.push(chunk);
+    }
+
+    program.natives = globals.natives;
+    Ok(program)
+}
+
+// Compiles a function, whose arguments are its first variables.
+fn compile_function(globals: &mut Globals, def: &'static ast::Function) -> Result<Chunk> {
+    let mut compiler = Compiler::new(globals);
+    for arg in &def.args {
+        compiler.declare(arg);
+    }
+    compiler.block(&def.body)?;
+    Ok(compiler.chunk)
+}
+
+// Compiles a system, whose resources, events, entity and components are its first variables.
+fn compile_system(globals: &mut Globals, sys: &'static ast::System) -> Result<SystemCode> {
+    let mut compiler = Compiler::new(globals);
+    let resources = sys.filter.resources.iter().map(|arg| compiler.declare(arg.name)).collect();
+    let events = sys.filter.events.iter().map(|arg| compiler.declare(arg.name)).collect();
+    let entities = sys.filter.entities.as_ref().map(|filter| compiler.binding(filter));
+    compiler.block(&sys.code)?;
+
+    Ok(SystemCode {
+        sys,
+        resources,
+        events,
+        entities,
+        chunk: compiler.chunk,
+    })
+}
+
+// What is shared by the compilation of all the chunks.
+struct Globals<'a> {
+    ctx: &'a Context,
+    functions: Map<usize>,
+    natives: Vec<(&'static str, Native)>,
+    native_ids: Map<usize>,
+    // The chunks of the anonymous functions compiled so far.
+    closures: Vec<(&'static ast::Function, Chunk)>,
+}
+
+// The jumps to patch at the end of a loop or a query.
+enum Target {
+    Loop {
+        breaks: Vec<usize>,
+        continues: Vec<usize>,
+    },
+    // Breaking out of a query and returning from it are the same.
+    Query {
+        exits: Vec<usize>,
+        next: usize,
+    },
+}
+
+// Compiles a chunk.
+struct Compiler<'a, 'b> {
+    globals: &'b mut Globals<'a>,
+    chunk: Chunk,
+    // The names of the variables in scope, by level, with their slots.
+    scopes: Vec<Vec<(&'static str, usize)>>,
+    next_slot: usize,
+    targets: Vec<Target>,
+}
+
+impl<'a, 'b> Compiler<'a, 'b> {
+    // Creates a compiler, with one empty scope level.
+    fn new(globals: &'b mut Globals<'a>) -> Self {
+        Compiler {
+            globals,
+            chunk: Chunk::default(),
+            scopes: vec![Vec::new()],
+            next_slot: 0,
+            targets: Vec::new(),
+        }
+    }
+
+    // Adds an instruction, returning its address.
+    fn emit(&mut self, op: Op, span: ast::Span) -> usize {
+        self.chunk.ops.push(op);
+        self.chunk.spans.push(span);
+        self.chunk.ops.len() - 1
+    }
+
+    // The address of the next instruction.
+    fn here(&self) -> usize {
+        self.chunk.ops.len()
+    }
+
+    // Makes the jump at the given address go to the next instruction.
+    fn patch(&mut self, at: usize) {
+        let to = self.here();
+        match &mut self.chunk.ops[at] {
+            Op::Jump(addr) | Op::Test(addr, _) | Op::Case(_, addr) | Op::CaseVariant(_, addr) | Op::QueryNext(_, addr) => *addr = to,
+            op => unreachable!("{:?} is not a jump", op),
+        }
+    }
+
+    // Adds a constant, returning its index.
+    fn constant(&mut self, var: Var) -> usize {
+        self.chunk.consts.push(var);
+        self.chunk.consts.len() - 1
+    }
+
+    // Nests a new scope level.
+    fn enter(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    // Leaves the last scope level, its slots can then be reused.
+    fn leave(&mut self) {
+        let level = self.scopes.pop().unwrap();
+        self.next_slot -= level.len();
+    }
+
+    // Declares a variable in the topmost scope level, returning its slot.
+    fn declare(&mut self, name: &'static str) -> usize {
+        let slot = self.next_slot;
+        self.scopes.last_mut().unwrap().push((name, slot));
+        self.next_slot += 1;
+        self.chunk.slots = self.chunk.slots.max(self.next_slot);
+        slot
+    }
+
+    // Finds the slot of the variable with the given name, the last declared one winning.
+    fn resolve(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev()
+            .flat_map(|level| level.iter().rev())
+            .find(|(var, _)| *var == name)
+            .map(|(_, slot)| *slot)
+    }
+
+    // Declares the variables an entity filter binds.
+    fn binding(&mut self, filter: &'static ast::EntityFilter) -> Binding {
+        Binding {
+            filter,
+            entity: self.declare(filter.name),
+            args: filter.args.iter().map(|arg| self.declare(arg.name)).collect(),
+            optional: filter.optional.iter().map(|arg| self.declare(arg.name)).collect(),
+        }
+    }
+
+    // Compiles a block, in its own scope level.
+    fn block(&mut self, block: &'static ast::Block) -> Result<()> {
+        self.enter();
+        for stmt in &block.statements {
+            self.statement(stmt)?;
+        }
+        self.leave();
+        Ok(())
+    }
+
+    // Compiles a statement.
+    fn statement(&mut self, stmt: &'static ast::Statement) -> Result<()> {
+        let span = stmt.span;
+        match &stmt.kind {
+            ast::StatementKind::Break => {
+                let at = self.emit(Op::Jump(0), span);
+                match self.targets.last_mut() {
+                    Some(Target::Loop {breaks, ..}) => breaks.push(at),
+                    Some(Target::Query {exits, ..}) => exits.push(at),
+                    None => self.chunk.ops[at] = Op::Break,
+                }
+            }
+            ast::StatementKind::Continue => {
+                let at = self.emit(Op::Jump(0), span);
+                match self.targets.last_mut() {
+                    Some(Target::Loop {continues, ..}) => continues.push(at),
+                    Some(Target::Query {next, ..}) => self.chunk.ops[at] = Op::Jump(*next),
+                    None => self.chunk.ops[at] = Op::Continue,
+                }
+            }
+            ast::StatementKind::Return(expr) => {
+                match expr {
+                    Some(expr) => self.expr(expr)?,
+                    None => {
+                        let void = self.constant(Var::Void);
+                        self.emit(Op::Const(void), span);
+                    }
+                }
+
+                // Returning from a query only stops it, like the evaluator does.
+                let query = self.targets.iter().rposition(|target| matches!(target, Target::Query {..}));
+                match query {
+                    Some(i) => {
+                        self.emit(Op::Pop, span);
+                        let at = self.emit(Op::Jump(0), span);
+                        if let Target::Query {exits, ..} = &mut self.targets[i] {
+                            exits.push(at);
+                        }
+                    }
+                    None => { self.emit(Op::Return, span); },
+                }
+            }
+            ast::StatementKind::Expr(expr) => {
+                self.expr(expr)?;
+                self.emit(Op::Pop, span);
+            }
+            ast::StatementKind::If(if_) => self.if_(if_, span)?,
+            ast::StatementKind::Block(block) => self.block(block)?,
+            ast::StatementKind::Decl(decl) => self.decl(decl, span)?,
+            ast::StatementKind::For(for_) => self.for_(for_, span)?,
+            ast::StatementKind::While(while_) => self.while_(while_, span)?,
+            ast::StatementKind::Query(query) => self.query(query, span)?,
+            ast::StatementKind::Switch(switch) => self.switch(switch, span)?,
+        }
+        Ok(())
+    }
+
+    // Compiles an if statement.
+    fn if_(&mut self, if_: &'static ast::If, span: ast::Span) -> Result<()> {
+        self.expr(&if_.cond)?;
+        let test = self.emit(Op::Test(0, "an if statement"), if_.cond.span);
+        self.block(&if_.branch1)?;
+
+        match &if_.branch2 {
+            Some(branch2) => {
+                let jump = self.emit(Op::Jump(0), span);
+                self.patch(test);
+                self.block(branch2)?;
+                self.patch(jump);
+            }
+            None => self.patch(test),
+        }
+        Ok(())
+    }
+
+    // Compiles a declaration, the new variable is not in scope of its initialization.
+    fn decl(&mut self, decl: &'static ast::Decl, span: ast::Span) -> Result<()> {
+        match &decl.init {
+            Some(init) => self.expr(init)?,
+            None => {
+                let void = self.constant(Var::Void);
+                self.emit(Op::Const(void), span);
+            }
+        }
+        let slot = self.declare(decl.ident);
+        self.emit(Op::Decl(slot), span);
+        Ok(())
+    }
+
+    // Compiles the body of a loop, and patches its breaks and continues.
+    fn loop_body(&mut self, code: &'static ast::Block) -> Result<(Vec<usize>, Vec<usize>)> {
+        self.targets.push(Target::Loop {breaks: Vec::new(), continues: Vec::new()});
+        self.block(code)?;
+        match self.targets.pop() {
+            Some(Target::Loop {breaks, continues}) => Ok((breaks, continues)),
+            _ => unreachable!(),
+        }
+    }
+
+    // Compiles a for loop.
+    fn for_(&mut self, for_: &'static ast::For, span: ast::Span) -> Result<()> {
+        self.enter();
+
+        match &for_.init {
+            Either::Left(expr) => {
+                self.expr(expr)?;
+                self.emit(Op::Pop, span);
+            }
+            Either::Right(decl) => self.decl(decl, span)?,
+        }
+
+        let start = self.here();
+        self.expr(&for_.cond)?;
+        let test = self.emit(Op::Test(0, "a for loop"), for_.cond.span);
+        let (breaks, continues) = self.loop_body(&for_.code)?;
+
+        for at in continues {
+            self.patch(at);
+        }
+        self.expr(&for_.incr)?;
+        self.emit(Op::Pop, span);
+        self.emit(Op::Jump(start), span);
+
+        for at in breaks.into_iter().chain(Some(test)) {
+            self.patch(at);
+        }
+
+        self.leave();
+        Ok(())
+    }
+
+    // Compiles a while loop.
+    fn while_(&mut self, while_: &'static ast::While, span: ast::Span) -> Result<()> {
+        self.enter();
+
+        let start = self.here();
+        self.expr(&while_.cond)?;
+        let test = self.emit(Op::Test(0, "a while loop"), while_.cond.span);
+        let (breaks, continues) = self.loop_body(&while_.code)?;
+
+        for at in continues {
+            self.chunk.ops[at] = Op::Jump(start);
+        }
+        self.emit(Op::Jump(start), span);
+
+        for at in breaks.into_iter().chain(Some(test)) {
+            self.patch(at);
+        }
+
+        self.leave();
+        Ok(())
+    }
+
+    // Compiles a query.
+    fn query(&mut self, query: &'static ast::Query, span: ast::Span) -> Result<()> {
+        self.enter();
+
+        let binding = self.binding(&query.filter);
+        self.chunk.queries.push(binding);
+        let id = self.chunk.queries.len() - 1;
+
+        self.emit(Op::QueryStart(id), span);
+        let next = self.emit(Op::QueryNext(id, 0), span);
+
+        self.targets.push(Target::Query {exits: Vec::new(), next});
+        self.block(&query.code)?;
+        let exits = match self.targets.pop() {
+            Some(Target::Query {exits, ..}) => exits,
+            _ => unreachable!(),
+        };
+        self.emit(Op::Jump(next), span);
+
+        for at in exits.into_iter().chain(Some(next)) {
+            self.patch(at);
+        }
+        self.emit(Op::QueryEnd, span);
+
+        self.leave();
+        Ok(())
+    }
+
+    // Compiles a switch. The value stays on the stack until a case matches.
+    fn switch(&mut self, switch: &'static ast::Switch, span: ast::Span) -> Result<()> {
+        self.expr(&switch.expr)?;
+
+        let mut ends = Vec::with_capacity(switch.cases.len());
+        for case in &switch.cases {
+            let test = match &case.pattern {
+                ast::Pattern::Atom(atom) => {
+                    let id = self.constant(eval_atom(atom)?);
+                    let test = self.emit(Op::Case(id, 0), case.span);
+                    self.block(&case.block)?;
+                    test
+                }
+                ast::Pattern::Variant(pattern) => {
+                    self.enter();
+                    let slots = pattern.bindings.iter().map(|name| self.declare(name)).collect();
+                    self.chunk.patterns.push((pattern, slots));
+                    let test = self.emit(Op::CaseVariant(self.chunk.patterns.len() - 1, 0), case.span);
+                    self.block(&case.block)?;
+                    self.leave();
+                    test
+                }
+            };
+            ends.push(self.emit(Op::Jump(0), case.span));
+            self.patch(test);
+        }
+
+        self.emit(Op::Pop, span);
+        if let Some(block) = &switch.default {
+            self.block(block)?;
+        }
+
+        for at in ends {
+            self.patch(at);
+        }
+        Ok(())
+    }
+
+    // Compiles an expression, whose value is pushed on the stack.
+    fn expr(&mut self, expr: &'static ast::Expr) -> Result<()> {
+        let span = expr.span;
+        match &expr.kind {
+            ast::ExprKind::Ternary(ternary) => {
+                self.expr(&ternary.cond)?;
+                let test = self.emit(Op::Test(0, "an if statement"), span);
+                self.expr(&ternary.branch1)?;
+                let jump = self.emit(Op::Jump(0), span);
+                self.patch(test);
+                self.expr(&ternary.branch2)?;
+                self.patch(jump);
+            }
+            ast::ExprKind::Assign(assign) => {
+                let lspan = assign.lvalue.span;
+                self.expr(&assign.expr)?;
+                self.place(&assign.lvalue)?;
+                if let Some(op) = assign.op {
+                    self.emit(Op::PlaceGet, lspan);
+                    self.emit(Op::Swap, span);
+                    self.emit(Op::BinOp(op), span);
+                }
+                self.emit(Op::PlaceSet, lspan);
+            }
+            ast::ExprKind::Incr(incr) => {
+                let lspan = incr.lvalue.span;
+                self.place(&incr.lvalue)?;
+                self.emit(Op::PlaceGet, lspan);
+                self.emit(Op::Incr(incr.op), span);
+                self.emit(Op::PlaceSet, lspan);
+                if incr.prefix {
+                    self.emit(Op::Swap, span);
+                }
+                self.emit(Op::Pop, span);
+            }
+            ast::ExprKind::Atom(atom) => {
+                let id = self.constant(eval_atom(atom)?);
+                self.emit(Op::Const(id), span);
+            }
+            ast::ExprKind::LValue(lvalue) => self.lvalue(lvalue)?,
+            ast::ExprKind::ListInit(list_init) => {
+                for expr in &list_init.exprs {
+                    self.expr(expr)?;
+                }
+                self.emit(Op::List(list_init.exprs.len()), span);
+            }
+            ast::ExprKind::StructInit(struct_init) => self.struct_init(struct_init, span)?,
+            ast::ExprKind::EnumInit(enum_init) => self.enum_init(enum_init, span)?,
+            ast::ExprKind::Call(call) => self.call(call).at(call.span)?,
+            ast::ExprKind::Function(def) => self.closure(def, span)?,
+            ast::ExprKind::BinExpr(bin_expr) => {
+                self.expr(&bin_expr.left)?;
+                self.expr(&bin_expr.right)?;
+                self.emit(Op::BinOp(bin_expr.op), span);
+            }
+            ast::ExprKind::UnExpr(un_expr) => {
+                self.expr(&un_expr.expr)?;
+                self.emit(Op::UnOp(un_expr.op), span);
+            }
+        }
+        Ok(())
+    }
+
+    // Compiles the indexing of the value on top of the stack.
+    fn index(&mut self, index: &'static ast::Index, span: ast::Span) -> Result<()> {
+        for expr in &index.exprs {
+            self.expr(expr)?;
+            self.emit(Op::Index, span);
+        }
+        Ok(())
+    }
+
+    // Compiles a left value, to read it.
+    fn lvalue(&mut self, lvalue: &'static ast::LValue) -> Result<()> {
+        let span = lvalue.span;
+        match self.resolve(lvalue.name) {
+            Some(slot) => { self.emit(Op::Load(slot), span); },
+            // The name of a struct-like type, that isn't shadowed by a variable, evaluates to a string,
+            // and the name of a function to the function.
+            None if lvalue.first_index.exprs.is_empty() && lvalue.path.is_empty() => {
+                let var = match self.globals.ctx.get_def_entry(lvalue.name) {
+                    Ok((_, Def::Component(_) | Def::Resource(_) | Def::Event(_) | Def::Struct(_))) => Var::String(lvalue.name.to_string()),
+                    Ok((name, Def::Function(def))) => function_value(name, def),
+                    _ => {
+                        self.emit(Op::Undefined(lvalue.name), span);
+                        return Ok(());
+                    }
+                };
+                let id = self.constant(var);
+                self.emit(Op::Const(id), span);
+                return Ok(());
+            }
+            None => { self.emit(Op::Undefined(lvalue.name), span); },
+        }
+
+        self.index(&lvalue.first_index, span)?;
+        for (name, index) in &lvalue.path {
+            self.emit(Op::Field(name), span);
+            self.index(index, span)?;
+        }
+        Ok(())
+    }
+
+    // Compiles the location a left value refers to, evaluating its indices once.
+    fn place(&mut self, lvalue: &'static ast::LValue) -> Result<()> {
+        let span = lvalue.span;
+        let var = match self.resolve(lvalue.name) {
+            Some(slot) => Op::Load(slot),
+            None => Op::Undefined(lvalue.name),
+        };
+
+        // The indices and fields to follow, in order.
+        let mut steps = lvalue.first_index.exprs.iter().map(Either::Left)
+            .chain(lvalue.path.iter().flat_map(|(name, index)| {
+                std::iter::once(Either::Right(*name)).chain(index.exprs.iter().map(Either::Left))
+            }));
+
+        let mut last = match steps.next() {
+            Some(step) => step,
+            None => {
+                self.emit(match var {
+                    Op::Load(slot) => Op::PlaceLocal(slot),
+                    op => op,
+                }, span);
+                return Ok(());
+            }
+        };
+
+        // The variable stays below the path, for its place to know where it started.
+        self.emit(var, span);
+        self.emit(Op::Dup, span);
+        for step in steps {
+            match last {
+                Either::Left(expr) => {
+                    self.expr(expr)?;
+                    self.emit(Op::Index, span);
+                }
+                Either::Right(name) => { self.emit(Op::Field(name), span); },
+            }
+            last = step;
+        }
+
+        match last {
+            Either::Left(expr) => {
+                self.emit(Op::CheckList, span);
+                self.expr(expr)?;
+                self.emit(Op::PlaceIndex, span);
+            }
+            Either::Right(name) => { self.emit(Op::PlaceField(name), span); },
+        }
+        Ok(())
+    }
+
+    // Compiles a struct initialization, checking the type of each field once it is evaluated.
+    fn struct_init(&mut self, struct_init: &'static ast::StructInit, span: ast::Span) -> Result<()> {
+        let def = match self.globals.ctx.get_def(struct_init.name).at(span)? {
+            Def::Component(def) | Def::Resource(def) | Def::Event(def) | Def::Struct(def) => def,
+            _ => return Err(anyhow!("{} is not a struct type.", struct_init.name)).at(span),
+        };
+
+        for (name, expr) in &struct_init.fields {
+            let ty = def.fields.get(name)
+                .ok_or_else(|| anyhow!("{} is not a field of {}.", name, struct_init.name)).at(span)?;
+            self.expr(expr)?;
+            self.emit(Op::FieldType(struct_init.name, name, *ty), expr.span);
+        }
+
+        self.emit(Op::Struct(struct_init), span);
+        Ok(())
+    }
+
+    // Compiles an enum value, checking the type of each value once it is evaluated.
+    fn enum_init(&mut self, enum_init: &'static ast::EnumInit, span: ast::Span) -> Result<()> {
+        let (name, def) = match self.globals.ctx.get_def_entry(enum_init.name).at(span)? {
+            (name, Def::Enum(def)) => (name, def),
+            _ => return Err(anyhow!("{} is not an enum.", enum_init.name)).at(span),
+        };
+
+        let variant = def.variant(enum_init.variant)
+            .ok_or_else(|| anyhow!("{} is not a variant of {}.", enum_init.variant, name)).at(span)?;
+
+        for (ty, expr) in variant.fields.iter().zip(enum_init.args.iter()) {
+            self.expr(expr)?;
+            self.emit(Op::PayloadType(name, variant.name, *ty), expr.span);
+        }
+
+        self.emit(Op::Enum(name, variant.name, enum_init.args.len()), span);
+        Ok(())
+    }
+
+    // Compiles an anonymous function to a chunk of its own. It captures the variables in scope that its
+    // arguments don't shadow, sorted by name as the evaluator does, and they come after its arguments.
+    fn closure(&mut self, def: &'static ast::Function, span: ast::Span) -> Result<()> {
+        let mut captures: Vec<(&'static str, usize)> = Vec::new();
+        for (name, slot) in self.scopes.iter().rev().flat_map(|level| level.iter().rev()) {
+            if !def.args.contains(name) && !captures.iter().any(|(captured, _)| captured == name) {
+                captures.push((name, *slot));
+            }
+        }
+        captures.sort_by_key(|(name, _)| *name);
+
+        let mut compiler = Compiler::new(self.globals);
+        for arg in &def.args {
+            compiler.declare(arg);
+        }
+        for (name, _) in &captures {
+            compiler.declare(name);
+        }
+        compiler.block(&def.body)?;
+        let chunk = compiler.chunk;
+        self.globals.closures.push((def, chunk));
+
+        self.chunk.closures.push((def, captures));
+        self.emit(Op::Closure(self.chunk.closures.len() - 1), span);
+        Ok(())
+    }
+
+    // Compiles a call, to a native function first, then to a function, or to a variable holding a function.
+    fn call(&mut self, call: &'static ast::Call) -> Result<()> {
+        let ast::Call {name, args, span} = call;
+
+        let (op, def) = if let Some(native) = self.globals.ctx.get_native(name) {
+            let natives = &mut self.globals.natives;
+            let id = *self.globals.native_ids.entry(name).or_insert_with(|| {
+                natives.push((name, native.clone()));
+                natives.len() - 1
+            });
+            (Op::Native(id, args.len()), None)
+        } else {
+            match self.globals.ctx.get_def(name) {
+                Ok(Def::Function(def)) if def.args.len() == args.len() => (Op::Call(self.globals.functions[name], args.len()), Some(def)),
+                Ok(Def::Function(def)) => return Err(anyhow!(
+                    "{} expected exactly {} arguments, but {} where provided", name, def.args.len(), args.len()
+                )),
+                Ok(_) => return Err(anyhow!("{} is not a function.", name)),
+                // A variable holding a function, whose arguments are checked once it is known.
+                Err(err) => {
+                    let slot = self.resolve(name).ok_or(err)?;
+                    self.emit(Op::Load(slot), *span);
+                    (Op::CallValue(name, args.len()), None)
+                }
+            }
+        };
+
+        // The types declared by the function are checked around the call.
+        for (i, arg) in args.iter().enumerate() {
+            self.expr(arg)?;
+            if let Some(ty) = def.and_then(|def| def.types[i]) {
+                self.emit(Op::ArgType(name, i, ty), arg.span);
+            }
+        }
+        self.emit(op, *span);
+        if let Some(ty) = def.and_then(|def| def.returns) {
+            self.emit(Op::ReturnType(name, ty), *span);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+use std::rc::Rc;
+
+use super::*;
+
+// How the execution of a chunk ended.
+enum Exit {
+    End,
+    Return(Var),
+    Break,
+    Continue,
+}
+
+// A location a value can be assigned to.
+enum Place {
+    Local(usize),
+    Index(Shared<Vec<Var>>, usize, Var),
+    Field(Shared<Struct>, &'static str, Var),
+}
+
+// The state of the execution of a chunk.
+struct Frame {
+    locals: Vec<Var>,
+    stack: Vec<Var>,
+    places: Vec<Place>,
+    // The entities of the running queries, with the index of the next one.
+    queries: Vec<(Vec<Entity>, usize)>,
+}
+
+impl Frame {
+    // Creates a frame, with the given first variables.
+    fn new(chunk: &Chunk, mut locals: Vec<Var>) -> Self {
+        locals.resize(chunk.slots.max(locals.len()), Var::Void);
+        Frame {
+            locals,
+            stack: Vec::new(),
+            places: Vec::new(),
+            queries: Vec::new(),
+        }
+    }
+
+    // Pops the top of the stack, the compiler makes sure there is one.
+    fn pop(&mut self) -> Var {
+        self.stack.pop().unwrap()
+    }
+
+    // Pops the given number of values, in the order they were pushed.
+    fn pop_n(&mut self, n: usize) -> Vec<Var> {
+        self.stack.split_off(self.stack.len() - n)
+    }
+
+    // Gets the top of the stack.
+    fn top(&self) -> &Var {
+        self.stack.last().unwrap()
+    }
+}
+
+impl Program {
+    // Runs a system by its name.
+    pub fn run_system(&self, ctx: &Context, name: &str) -> Result<()> {
+        match (ctx.get_def(name)?, self.systems.get_key_value(name)) {
+            (Def::System(_), Some((name, code))) => ctx.in_system(name, || self.system(ctx, code).at(code.sys.span)),
+            _ => Err(anyhow!("{} is not a system", name)),
+        }
+    }
+
+    // Runs a system, without locating the errors.
+    fn system(&self, ctx: &Context, code: &SystemCode) -> Result<()> {
+        let sys = code.sys;
+
+        // Skips the system if one of the resources it needs doesn't exist.
+        for arg in &sys.filter.resources {
+            if !ctx.world().has_resource(arg.ty)? {
+                return Ok(());
+            }
+        }
+
+        let matches = match &code.entities {
+            Some(binding) => Some(ctx.world_mut().filter_entities(binding.filter)?),
+            None => None,
+        };
+
+        // The events are read once, for all the entities, each getting its own copy. They are only
+        // read when the code runs, so that they are still there the next time if no entity matches.
+        let events = match matches.as_ref().is_some_and(|matches| matches.is_empty()) {
+            true => Vec::new(),
+            false => sys.filter.events.iter().map(|arg| ctx.read_events(arg.ty)).collect(),
+        };
+
+        // The resources and the events are the first variables of the system.
+        let resources = || -> Result<Vec<Var>> {
+            let mut locals = vec![Var::Void; code.chunk.slots];
+            for (arg, slot) in sys.filter.resources.iter().zip(&code.resources) {
+                locals[*slot] = ctx.world().get_resource(arg.ty)?;
+            }
+            for (var, slot) in events.iter().zip(&code.events) {
+                locals[*slot] = var.deep_copy();
+            }
+            Ok(locals)
+        };
+
+        if let (Some(binding), Some(matches)) = (&code.entities, matches) {
+            for entity in matches {
+                let mut locals = resources()?;
+                bind(ctx, binding, &mut locals, entity)?;
+
+                match self.execute(ctx, &code.chunk, locals)? {
+                    Exit::Return(_) => return Err(anyhow!("Systems can't return.")),
+                    Exit::Break => break,
+                    _ => (),
+                }
+            }
+        } else if let Exit::Return(_) = self.execute(ctx, &code.chunk, resources()?)? {
+            return Err(anyhow!("Systems can't return."));
+        }
+
+        // Apply the commands to the world.
+        ctx.update()
+    }
+
+    // Executes a chunk from its start.
+    fn execute(&self, ctx: &Context, chunk: &Chunk, locals: Vec<Var>) -> Result<Exit> {
+        let mut frame = Frame::new(chunk, locals);
+        let mut pc = 0;
+
+        while pc < chunk.ops.len() {
+            let at = pc;
+            pc += 1;
+            if let Some(exit) = self.step(ctx, chunk, &mut frame, chunk.ops[at], &mut pc).at(chunk.spans[at])? {
+                return Ok(exit);
+            }
+        }
+
+        Ok(Exit::End)
+    }
+
+    // Executes an instruction, returning how the chunk ended if it did.
+    fn step(&self, ctx: &Context, chunk: &Chunk, frame: &mut Frame, op: Op, pc: &mut usize) -> Result<Option<Exit>> {
+        match op {
+            Op::Const(id) => frame.stack.push(chunk.consts[id].clone()),
+            Op::Load(slot) => frame.stack.push(frame.locals[slot].clone()),
+            Op::Decl(slot) => frame.locals[slot] = frame.pop(),
+            Op::Undefined(name) => return Err(anyhow!("Variable {} does not exist in current ctx.", name)),
+            Op::Pop => { frame.pop(); },
+            Op::Dup => frame.stack.push(frame.top().clone()),
+            Op::Swap => {
+                let len = frame.stack.len();
+                frame.stack.swap(len - 1, len - 2);
+            }
+            Op::List(n) => {
+                let list = frame.pop_n(n);
+                frame.stack.push(Var::List(as_shared(list)));
+            }
+            Op::Struct(struct_init) => {
+                let vars = frame.pop_n(struct_init.fields.len());
+                let map = struct_init.fields.iter().map(|(name, _)| *name).zip(vars).collect();
+                frame.stack.push(Var::Struct(as_shared(Struct {
+                    name: struct_init.name,
+                    map,
+                })));
+            }
+            Op::FieldType(struct_name, name, ty) => check_field(struct_name, name, &ty, frame.top())?,
+            Op::Enum(name, variant, n) => {
+                let args = frame.pop_n(n);
+                frame.stack.push(Var::Enum(Rc::new(Enum {name, variant, args})));
+            }
+            Op::PayloadType(name, variant, ty) => check_payload(name, variant, &ty, frame.top())?,
+            Op::BinOp(op) => {
+                let rvar = frame.pop();
+                let lvar = frame.pop();
+                frame.stack.push(apply_bin_op(lvar, op, rvar)?);
+            }
+            Op::UnOp(op) => {
+                let var = frame.pop();
+                frame.stack.push(apply_un_op(op, var)?);
+            }
+            Op::Index => {
+                let i = as_index(frame.pop())?;
+                let list = frame.pop();
+                frame.stack.push(get_item(list, i)?);
+            }
+            Op::Field(name) => {
+                let var = frame.pop();
+                frame.stack.push(get_struct(var, name)?);
+            }
+            Op::PlaceLocal(slot) => frame.places.push(Place::Local(slot)),
+            Op::CheckList => if !matches!(frame.top(), Var::List(_)) {
+                return Err(anyhow!("Expected a list."));
+            },
+            Op::PlaceIndex => {
+                let i = as_index(frame.pop())?;
+                let list = frame.pop();
+                let root = frame.pop();
+                match list {
+                    Var::List(list) => frame.places.push(Place::Index(list, i, root)),
+                    _ => return Err(anyhow!("Expected a list.")),
+                }
+            }
+            Op::PlaceField(name) => {
+                let s = frame.pop();
+                let root = frame.pop();
+                match s {
+                    Var::Struct(s) => frame.places.push(Place::Field(s, name, root)),
+                    _ => return Err(anyhow!("Expected a struct.")),
+                }
+            }
+            Op::PlaceGet => {
+                let var = match frame.places.last().unwrap() {
+                    Place::Local(slot) => frame.locals[*slot].clone(),
+                    Place::Index(list, i, _) => get_at(list, *i)?,
+                    Place::Field(s, name, _) => get_struct(Var::Struct(s.clone()), name)?,
+                };
+                frame.stack.push(var);
+            }
+            Op::PlaceSet => {
+                let val = frame.top().clone();
+                match frame.places.pop().unwrap() {
+                    Place::Local(slot) => {
+                        check_reassign(&frame.locals[slot])?;
+                        frame.locals[slot] = val;
+                    }
+                    Place::Index(list, i, root) => {
+                        set_at(&list, i, val)?;
+                        set_root_changed(ctx, &root);
+                    }
+                    Place::Field(s, name, root) => {
+                        set_field(&s, name, val)?;
+                        ctx.world_mut().set_changed(&s);
+                        set_root_changed(ctx, &root);
+                    }
+                }
+            }
+            Op::Incr(op) => {
+                let new = incremented(frame.top(), op)?;
+                frame.stack.push(new);
+            }
+            Op::Jump(addr) => *pc = addr,
+            Op::Test(addr, construct) => match frame.pop() {
+                Var::Bool(true) => (),
+                Var::Bool(false) => *pc = addr,
+                _ => return Err(anyhow!("A condition expression evaluated to a non-boolean value in {}.", construct)),
+            },
+            Op::Case(id, addr) => match *frame.top() == chunk.consts[id] {
+                true => { frame.pop(); },
+                false => *pc = addr,
+            },
+            Op::CaseVariant(id, addr) => {
+                let (pattern, slots) = &chunk.patterns[id];
+                match frame.top() {
+                    Var::Enum(e) if e.name == pattern.name && e.variant == pattern.variant => {
+                        let e = e.clone();
+                        frame.pop();
+                        for (slot, var) in slots.iter().zip(e.args.iter()) {
+                            frame.locals[*slot] = var.clone();
+                        }
+                    }
+                    _ => *pc = addr,
+                }
+            }
+            Op::Call(id, n) => {
+                let args = frame.pop_n(n);
+                let val = returned(self.execute(ctx, &self.functions[id], args)?)?;
+                frame.stack.push(val);
+            }
+            Op::Closure(id) => {
+                let (def, captures) = &chunk.closures[id];
+                let captures = captures.iter().map(|(name, slot)| (*name, frame.locals[*slot].clone())).collect();
+                frame.stack.push(Var::Function(Rc::new(Closure {name: None, def, captures})));
+            }
+            Op::CallValue(name, n) => {
+                let args = frame.pop_n(n);
+                let var = frame.pop();
+                let closure = as_function(name, &var)?;
+                check_args(name, closure.def, &args)?;
+
+                // The captured variables come right after the arguments.
+                let mut locals = args;
+                locals.extend(closure.captures.iter().map(|(_, var)| var.clone()));
+                // All the functions are compiled with the program, a function value from elsewhere is an error.
+                let id = self.chunks.get(&(closure.def as *const _))
+                    .ok_or_else(|| anyhow!("Function {} was not compiled with the program.", name))?;
+                let chunk = &self.functions[*id];
+                let val = returned(self.execute(ctx, chunk, locals)?)?;
+                check_return(name, &closure.def.returns, &val)?;
+                frame.stack.push(val);
+            }
+            Op::ArgType(name, i, ty) => check_arg(name, i, &Some(ty), frame.top())?,
+            Op::ReturnType(name, ty) => check_return(name, &Some(ty), frame.top())?,
+            Op::Native(id, n) => {
+                let (name, native) = &self.natives[id];
+                let args = frame.pop_n(n);
+                frame.stack.push(native.call(ctx, name, args)?);
+            }
+            Op::Return => return Ok(Some(Exit::Return(frame.pop()))),
+            Op::Break => return Ok(Some(Exit::Break)),
+            Op::Continue => return Ok(Some(Exit::Continue)),
+            Op::QueryStart(id) => {
+                let matches = ctx.world_mut().filter_entities(chunk.queries[id].filter)?;
+                frame.queries.push((matches, 0));
+            }
+            Op::QueryNext(id, addr) => {
+                let (matches, next) = frame.queries.last_mut().unwrap();
+                match matches.get(*next) {
+                    Some(entity) => {
+                        let entity = entity.clone();
+                        *next += 1;
+                        bind(ctx, &chunk.queries[id], &mut frame.locals, entity)?;
+                    }
+                    None => *pc = addr,
+                }
+            }
+            Op::QueryEnd => { frame.queries.pop(); },
+        }
+        Ok(None)
+    }
+}
+
+// Gets the value returned by a function, from the way its chunk ended.
+fn returned(exit: Exit) -> Result<Var> {
+    match exit {
+        Exit::Return(val) => Ok(val),
+        Exit::Break => Err(anyhow!("Cannot break outside of a loop.")),
+        Exit::Continue => Err(anyhow!("Cannot continue outside of a loop.")),
+        Exit::End => Ok(Var::Void),
+    }
+}
+
+// Puts the entity matched by a filter into its variables, along with its components.
+// Optional components the entity doesn't have are bound to void.
+fn bind(ctx: &Context, binding: &Binding, locals: &mut [Var], entity: Entity) -> Result<()> {
+    let world = ctx.world();
+
+    locals[binding.entity] = Var::Entity(entity.clone());
+
+    for (arg, slot) in binding.filter.args.iter().zip(&binding.args) {
+        locals[*slot] = world.get_component(entity.clone(), arg.ty)?;
+    }
+
+    for (arg, slot) in binding.filter.optional.iter().zip(&binding.optional) {
+        locals[*slot] = world.get_optional_component(entity.clone(), arg.ty)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+// A bytecode backend, running the same programs as the tree-walking evaluator.
+//
+// Functions and systems are compiled to chunks of instructions for a stack machine,
+// with variables resolved to numbered slots instead of being looked up by name.
+// The compiler relies on the checks of the check module, and must be run after them.
+
+use anyhow::{anyhow, Result};
+
+use crate::ast;
+use crate::eval::*;
+use crate::sources::WithSpan;
+use crate::utils::*;
+
+mod code;
+pub use code::*;
+
+mod compiler;
+pub use compiler::*;
+
+mod machine;
+pub use machine::*;
@@ -0,0 +1,312 @@
+// Runs C* programs with the interpreter and compares what they print with the expected outputs.
+//
+// For a program `name.cstar`, the directory of expectations may contain:
+// - `name.stdin`, the input given to the program, empty if missing;
+// - `name.stdout`, the expected standard output, empty if missing;
+// - `name.stderr`, the expected error output, empty if missing;
+// - `name.status`, the expected exit status, 0 if missing.
+//
+// Running the tests with the CSTAR_BLESS environment variable set writes the
+// expectations from the current outputs instead of comparing them.
+//
+// The programs are run both by the tree-walking evaluator and by the bytecode VM,
+// which must give the same outcomes. The REPL sessions of `tests/repl` are the
+// inputs `name.stdin` typed in the REPL, with the same kinds of expectations.
+// The language server sessions of `tests/lsp` are the messages of `name.jsonl`,
+// one per line, sent to the language server. The sources of `tests/fmt` are given
+// to the formatter, which outputs them formatted. The programs of `tests/loads` are
+// run with the world saved in `name.json` loaded first.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The time after which a program is considered stuck.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// What a program printed, and how it exited.
+#[derive(PartialEq, Debug)]
+struct Outcome {
+    stdout: String,
+    stderr: String,
+    status: Option<i32>,
+}
+
+// Returns true if the source is a module, meant to be included rather than run.
+// Modules are tested through the programs that include them.
+fn is_module(src: &str) -> bool {
+    !src.lines().any(|line| line.trim_start().starts_with("Init"))
+}
+
+// Runs the interpreter with the given arguments from the root of the crate, feeding it the given input.
+fn run(args: &[OsString], stdin: Vec<u8>) -> Outcome {
+    run_in(".", args, stdin)
+}
+
+// Runs the interpreter with the given arguments from a directory relative to the root of the crate,
+// feeding it the given input.
+fn run_in(dir: &str, args: &[OsString], stdin: Vec<u8>) -> Outcome {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cstar"))
+        .args(args)
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(dir))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The pipes are handled by other threads, so that a full pipe never blocks the program.
+    let mut input = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || input.write_all(&stdin));
+    let mut stdout = child.stdout.take().unwrap();
+    let stdout = thread::spawn(move || {
+        let mut buf = String::new();
+        stdout.read_to_string(&mut buf).map(|_| buf)
+    });
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut buf = String::new();
+        stderr.read_to_string(&mut buf).map(|_| buf)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status.code();
+        }
+        if start.elapsed() > TIMEOUT {
+            child.kill().unwrap();
+            child.wait().unwrap();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    // The program may exit without reading all of its input.
+    writer.join().unwrap().ok();
+
+    Outcome {
+        stdout: stdout.join().unwrap().unwrap(),
+        stderr: stderr.join().unwrap().unwrap(),
+        status,
+    }
+}
+
+// Reads the expected outcome of a program.
+fn expected(base: &Path) -> Outcome {
+    let read = |ext| fs::read_to_string(base.with_extension(ext)).unwrap_or_default();
+    Outcome {
+        stdout: read("stdout"),
+        stderr: read("stderr"),
+        status: Some(read("status").trim().parse().unwrap_or(0)),
+    }
+}
+
+// Writes the outcome of a program as its expectation, omitting the files holding default values.
+fn bless(base: &Path, outcome: &Outcome) {
+    let write = |ext, content: String, default: bool| {
+        let path = base.with_extension(ext);
+        match default {
+            true => drop(fs::remove_file(path)),
+            false => fs::write(path, content).unwrap(),
+        }
+    };
+    write("stdout", outcome.stdout.clone(), outcome.stdout.is_empty());
+    write("stderr", outcome.stderr.clone(), outcome.stderr.is_empty());
+    let status = outcome.status.expect("cannot bless a program that timed out");
+    write("status", format!("{}\n", status), status == 0);
+}
+
+// Lists the files of a directory with the given extension, sorted by name.
+// The path is relative to the root of the crate.
+fn files(dir: &str, extension: &str) -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(dir)).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+    assert!(!paths.is_empty(), "no .{} file found in {}", extension, dir);
+    paths
+}
+
+// Reads the input given to a program, empty if there is none.
+fn input(base: &Path) -> Vec<u8> {
+    fs::read(base.with_extension("stdin")).unwrap_or_default()
+}
+
+// Runs the interpreter with each list of arguments and input, comparing the outcomes
+// with the expectations at the paired base path, or blessing them.
+fn run_cases(cases: Vec<(Vec<OsString>, Vec<u8>, PathBuf)>) {
+    let blessing = env::var_os("CSTAR_BLESS").is_some();
+
+    let mut failures = Vec::new();
+    for (args, stdin, base) in cases {
+        let outcome = run(&args, stdin);
+
+        if blessing {
+            bless(&base, &outcome);
+            continue;
+        }
+
+        let expected = expected(&base);
+        if outcome != expected {
+            failures.push(format!(
+                "{}:\n--- expected {:?}\n{}{}--- got {:?}\n{}{}",
+                args.join(OsStr::new(" ")).to_string_lossy(),
+                expected.status, expected.stdout, expected.stderr,
+                outcome.status, outcome.stdout, outcome.stderr,
+            ));
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!("{} program(s) failed, run with CSTAR_BLESS=1 to update the expectations:\n\n{}", failures.len(), failures.join("\n"));
+    }
+}
+
+// Runs all the programs of a directory with the given flags, with the expectations in another one.
+// Both paths are relative to the root of the crate.
+fn run_all(programs: &str, expectations: &str, flags: &[&str]) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let cases = files(programs, "cstar").into_iter()
+        .filter(|path| !is_module(&fs::read_to_string(path).unwrap()))
+        .map(|path| {
+            let name = path.file_name().unwrap();
+            let mut args = flags.iter().map(OsString::from).collect::<Vec<_>>();
+            args.push(Path::new(programs).join(name).into());
+            let base = root.join(expectations).join(name);
+            (args, input(&base), base)
+        })
+        .collect();
+
+    run_cases(cases);
+}
+
+// Runs all the programs of a directory with the given flags, loading the world saved in the
+// `name.json` file next to each, with the expectations next to them too. The path is relative
+// to the root of the crate.
+fn run_loads(programs: &str, flags: &[&str]) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let cases = files(programs, "cstar").into_iter()
+        .map(|path| {
+            let program = Path::new(programs).join(path.file_name().unwrap());
+            let mut args = flags.iter().map(OsString::from).collect::<Vec<_>>();
+            args.extend([OsString::from("--load"), program.with_extension("json").into(), program.into()]);
+            (args, input(&path), root.join(path))
+        })
+        .collect();
+
+    run_cases(cases);
+}
+
+// Runs the REPL sessions of a directory, each typing the lines of a `name.stdin` file,
+// with the expectations next to it. The path is relative to the root of the crate.
+fn run_sessions(sessions: &str) {
+    let cases = files(sessions, "stdin").into_iter()
+        .map(|path| (vec![OsString::from("repl")], input(&path), path))
+        .collect();
+
+    run_cases(cases);
+}
+
+// Runs the language server sessions of a directory, each sending the messages of a
+// `name.jsonl` file, with the expectations next to it. The path is relative to the root of the crate.
+fn run_lsp_sessions(sessions: &str) {
+    let cases = files(sessions, "jsonl").into_iter()
+        .map(|path| {
+            let mut stdin = Vec::new();
+            for message in fs::read_to_string(&path).unwrap().lines().filter(|line| !line.trim().is_empty()) {
+                write!(stdin, "Content-Length: {}\r\n\r\n{}", message.len(), message).unwrap();
+            }
+            (vec![OsString::from("lsp")], stdin, path)
+        })
+        .collect();
+
+    run_cases(cases);
+}
+
+// Formats the sources of a directory, with the expectations next to them.
+// The path is relative to the root of the crate.
+fn run_formats(sources: &str) {
+    let cases = files(sources, "cstar").into_iter()
+        .map(|path| (vec![OsString::from("fmt"), OsString::from("-")], fs::read(&path).unwrap(), path))
+        .collect();
+
+    run_cases(cases);
+}
+
+#[test]
+fn examples() {
+    run_all("examples", "tests/examples", &[]);
+}
+
+#[test]
+fn errors() {
+    run_all("tests/errors", "tests/errors", &[]);
+}
+
+#[test]
+fn examples_vm() {
+    run_all("examples", "tests/examples", &["--vm"]);
+}
+
+#[test]
+fn errors_vm() {
+    run_all("tests/errors", "tests/errors", &["--vm"]);
+}
+
+#[test]
+fn loads() {
+    run_loads("tests/loads", &[]);
+}
+
+#[test]
+fn loads_vm() {
+    run_loads("tests/loads", &["--vm"]);
+}
+
+// Sources named like a subcommand are run, only the exact names are subcommands.
+#[test]
+fn subcommand_names() {
+    for name in ["repl.cstar", "replay.cstar", "fmt2.cstar", "lspx.cstar"] {
+        let outcome = run_in("tests/names", &[OsString::from(name)], Vec::new());
+        assert_eq!(outcome, Outcome {stdout: format!("{}\n", name), stderr: String::new(), status: Some(0)});
+    }
+}
+
+#[test]
+fn repl() {
+    run_sessions("tests/repl");
+}
+
+#[test]
+fn lsp() {
+    run_lsp_sessions("tests/lsp");
+}
+
+#[test]
+fn fmt() {
+    run_formats("tests/fmt");
+}
+
+#[test]
+fn formatted() {
+    let examples = files("examples", "cstar");
+    let mut args = vec![OsString::from("fmt"), OsString::from("--check")];
+    args.extend(examples.into_iter().map(PathBuf::into_os_string));
+    assert_eq!(run(&args, Vec::new()), Outcome {stdout: String::new(), stderr: String::new(), status: Some(0)});
+
+    let args = ["fmt", "--check", "tests/fmt/layout.cstar"].map(OsString::from);
+    assert_eq!(run(&args, Vec::new()), Outcome {
+        stdout: "tests/fmt/layout.cstar is not formatted.\n".to_string(),
+        stderr: String::new(),
+        status: Some(1),
+    });
+}